@@ -0,0 +1,91 @@
+// How long a step of the world takes with hundreds of foods and long worms
+// in a big arena, against the 16.7 ms of a frame at 60 fps.
+//
+//   cargo bench --bench world
+//
+// The worms have a shield so that the predators cannot end the round; they
+// still bounce off the bodies, which is one of the queries measured.
+
+// the game modules are shared with the window version, which uses more of them
+#![allow(dead_code)]
+
+#[path = "../worm5/consts.rs"]   mod consts;
+#[path = "../worm5/lead.rs"]     mod lead;
+#[path = "../worm5/unit.rs"]     mod unit;
+#[path = "../worm5/worm.rs"]     mod worm;
+#[path = "../worm5/food.rs"]     mod food;
+#[path = "../worm5/controls.rs"] mod controls;
+#[path = "../worm5/player.rs"]   mod player;
+#[path = "../worm5/spelling.rs"] mod spelling;
+#[path = "../worm5/ai.rs"]       mod ai;
+#[path = "../worm5/world.rs"]    mod world;
+#[path = "../worm5/level.rs"]    mod level;
+#[path = "../worm5/body.rs"]     mod body;
+#[path = "../worm5/creature.rs"] mod creature;
+#[path = "../worm5/power.rs"]    mod power;
+#[path = "../worm5/grid.rs"]     mod grid;
+
+use std::time::{Duration, Instant};
+
+use egui::{Vec2, vec2};
+
+use consts::PlayMode;
+use power::Power;
+use world::World;
+
+const ARENA:   Vec2  = vec2(3840.0, 2160.0);
+const PLAYERS: usize = 4;
+const UNITS:   usize = 60;  // grown onto every worm
+const FRAMES:  usize = 600; // ten seconds of play
+const WORDS: [&str; 2] = ["hippopotamus", "crocodile"];
+
+
+fn main() {
+    println!("{} worms of {} units, {} frames", PLAYERS, UNITS, FRAMES);
+    for predators in [100, 300, 600] {
+        let (foods, units, mean, worst) = run(predators);
+        println!("{:4} foods {:4} units   {:6.2} ms a step, {:6.2} ms at worst   (a 60 fps frame is 16.7 ms)",
+            foods, units, ms(mean), ms(worst));
+    }
+}
+
+fn run(predators: usize) -> (usize, usize, Duration, Duration) {
+    let mut world = World {
+        size:      ARENA,
+        predators,
+        letters_shown: 5,
+        ..Default::default()
+    };
+    world.start(PlayMode::OwnWords, WORDS.iter().map(|w| w.to_string()).collect(), None);
+    while world.players.len() < PLAYERS {
+        world.join();
+    }
+    for (i, p) in world.players.iter_mut().enumerate() {
+        p.worm.reset(vec2(200.0 + 800.0 * i as f32, 300.0 + 400.0 * i as f32), consts::PALETTES[i % consts::PALETTES.len()]);
+        for _ in 0..UNITS {
+            p.worm.grow(' ', None);
+        }
+        p.effects.push((Power::Shield, u32::MAX));
+    }
+
+    let mut total = Duration::ZERO;
+    let mut worst = Duration::ZERO;
+    for frame in 0..FRAMES {
+        // every worm swings round in its own wide circle
+        let forces: Vec<Vec2> = (0..world.players.len())
+            .map(|p| 0.15 * Vec2::angled(0.01 * frame as f32 + p as f32))
+            .collect();
+        let t0 = Instant::now();
+        world.step(&forces);
+        let dt = t0.elapsed();
+        total += dt;
+        worst = worst.max(dt);
+        world.events.clear();
+    }
+    let units = world.players.iter().map(|p| p.worm.units.len()).sum();
+    (world.foods.len(), units, total / FRAMES as u32, worst)
+}
+
+fn ms(d: Duration) -> f32 {
+    1000.0 * d.as_secs_f32()
+}
@@ -0,0 +1,88 @@
+// Bundles a vocabulary into a single .catpack file for the worm game.
+//
+//   caterpillar-pack build <pack.txt> <out.catpack> [sounds-dir]
+//   caterpillar-pack list  <file.catpack>
+//
+// pack.txt lists the name, the title audio and the words (see worm5/pack.rs).
+// For every word and the title the first of <name>.ogg, <name>.flac and
+// <name>.wav found in sounds-dir (default: sounds) is added.
+
+#[path = "../worm5/pack.rs"]
+mod pack;
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use pack::{Meta, Pack};
+
+const AUDIO_EXTENSIONS: [&str; 3] = ["ogg", "flac", "wav"];
+
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = match args.first().map(|s| s.as_str()) {
+        Some("build") if args.len() >= 3 => {
+            let sounds = args.get(3).map(|s| s.as_str()).unwrap_or("sounds");
+            build(Path::new(&args[1]), Path::new(&args[2]), Path::new(sounds))
+        }
+        Some("list") if args.len() == 2 => list(Path::new(&args[1])),
+        _ => {
+            eprintln!("usage: caterpillar-pack build <pack.txt> <out.catpack> [sounds-dir]");
+            eprintln!("       caterpillar-pack list  <file.catpack>");
+            return ExitCode::FAILURE;
+        }
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn build(meta_path: &Path, out: &Path, sounds: &Path) -> Result<(), String> {
+    let text = fs::read_to_string(meta_path).map_err(|e| format!("{}: {}", meta_path.display(), e))?;
+    let meta = Meta::parse(&text);
+    if meta.name.is_empty() {
+        return Err(format!("{}: missing 'name = ...'", meta_path.display()));
+    }
+    if meta.words.is_empty() {
+        return Err(format!("{}: no 'word = ...' lines", meta_path.display()));
+    }
+
+    let mut names: Vec<String> = meta.words.iter().map(|(w, _)| w.to_owned()).collect();
+    names.extend(meta.title.clone());
+
+    let mut pack = Pack::new(meta);
+    for name in names {
+        match find_audio(sounds, &name) {
+            Some(path) => {
+                let data = fs::read(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+                let file = path.file_name().unwrap().to_string_lossy();
+                pack.add_file(format!("sounds/{}", file), data);
+            }
+            None => eprintln!("warning: no audio for '{}'", name),
+        }
+    }
+    pack.write(out).map_err(|e| format!("{}: {}", out.display(), e))?;
+    println!("{}: {} words, {} files", out.display(), pack.meta.words.len(), pack.files.len());
+    Ok(())
+}
+
+fn list(path: &Path) -> Result<(), String> {
+    let pack = Pack::read(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    print!("{}", pack.meta.to_text());
+    for (name, data) in &pack.files {
+        println!("{:>10}  {}", data.len(), name);
+    }
+    Ok(())
+}
+
+fn find_audio(dir: &Path, name: &str) -> Option<PathBuf> {
+    AUDIO_EXTENSIONS.iter()
+        .map(|ext| dir.join(format!("{}.{}", name, ext)))
+        .find(|p| p.is_file())
+}
@@ -0,0 +1,284 @@
+// A headless server for the worm game. It runs the arena, owns the word,
+// the foods and the scores, and lets any number of games join over TCP:
+//
+//   caterpillar-server [address] [word,word,...] [level file]
+//
+// The address defaults to 0.0.0.0:7878, the words to the zoo animals,
+// the level to the open field.
+// Start a game, choose "Join server" and enter the address of this machine.
+
+// the game modules are shared with the window version, which uses more of them
+#![allow(dead_code)]
+
+#[path = "../worm5/consts.rs"]   mod consts;
+#[path = "../worm5/lead.rs"]     mod lead;
+#[path = "../worm5/unit.rs"]     mod unit;
+#[path = "../worm5/worm.rs"]     mod worm;
+#[path = "../worm5/food.rs"]     mod food;
+#[path = "../worm5/controls.rs"] mod controls;
+#[path = "../worm5/player.rs"]   mod player;
+#[path = "../worm5/spelling.rs"] mod spelling;
+#[path = "../worm5/ai.rs"]       mod ai;
+#[path = "../worm5/world.rs"]    mod world;
+#[path = "../worm5/net.rs"]      mod net;
+#[path = "../worm5/level.rs"]    mod level;
+#[path = "../worm5/body.rs"]     mod body;
+#[path = "../worm5/creature.rs"] mod creature;
+#[path = "../worm5/power.rs"]    mod power;
+#[path = "../worm5/grid.rs"]     mod grid;
+
+use std::env;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::mpsc::{self, Sender, SyncSender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use egui::{Vec2, vec2};
+use rand::Rng;
+
+use consts::ZOO_ANIMALS;
+use controls::{Action, Ramp};
+use level::Level;
+use net::ClientMsg;
+use world::{World, Event};
+
+const ARENA:       Vec2     = level::NORMAL_ARENA;
+const FRAME:       Duration = Duration::from_micros(16_667);
+const ROUND_PAUSE: u32      = 180; // frames between two rounds
+const OUTBOX:      usize    = 60;  // lines waiting for a client, about two seconds of play
+
+
+struct Client {
+    id:     usize,
+    stream: TcpStream,
+    outbox: SyncSender<String>,
+    player: usize,
+    ramp:   Ramp, // the player's own, once the game has sent it
+    action: Option<Action>,
+    point:  Option<Vec2>,
+}
+
+// what the reader threads pass to the main loop, None when a client leaves
+type Incoming = (usize, Option<String>);
+
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let addr = args.first().map(|s| s.as_str()).unwrap_or("0.0.0.0:7878");
+    let words: Vec<String> = match args.get(1) {
+        Some(list) => list.split(',').map(|w| w.trim().to_owned()).filter(|w| !w.is_empty()).collect(),
+        None => ZOO_ANIMALS.iter().map(|s| s.to_string()).collect(),
+    };
+    if words.is_empty() {
+        eprintln!("no words");
+        return;
+    }
+
+    let level = match args.get(2) {
+        Some(path) => match Level::load(Path::new(path)) {
+            Ok(l) => l,
+            Err(e) => {
+                eprintln!("cannot load {}: {}", path, e);
+                return;
+            }
+        },
+        None => Level::default(),
+    };
+
+    let listener = match TcpListener::bind(addr) {
+        Ok(l) => l,
+        Err(e) => {
+            eprintln!("cannot listen on {}: {}", addr, e);
+            return;
+        }
+    };
+    if let Err(e) = listener.set_nonblocking(true) {
+        eprintln!("cannot listen on {}: {}", addr, e);
+        return;
+    }
+    println!("listening on {}", addr);
+
+    let (tx, rx) = mpsc::channel::<Incoming>();
+    let mut clients: Vec<Client> = Vec::new();
+    let mut next_id = 0;
+    let mut world = World {
+        size:      ARENA,
+        obstacles: level.obstacles(ARENA),
+        spawns:    level.spawns(ARENA),
+        start:     level.start(ARENA),
+        predators: level.n_predators(),
+        letters_shown: level.n_shown(),
+        letter_kind:   level.letter_kind(),
+        predator_kind: level.predator_kind(),
+        power_kinds:   level.power_kinds(),
+        ..Default::default()
+    };
+    let level_line = net::level_line(&world.obstacles);
+    let mut word = String::new();
+    let mut pause: u32 = 0;
+    let mut frame: u64 = 0;
+
+    loop {
+        let t0 = Instant::now();
+
+        // newcomers
+        while let Ok((stream, peer)) = listener.accept() {
+            if let Err(e) = stream.set_nonblocking(false) {
+                eprintln!("cannot take {}: {}", peer, e);
+                continue;
+            }
+            stream.set_nodelay(true).ok();
+            let (Ok(reader), Ok(writer)) = (stream.try_clone(), stream.try_clone()) else { continue };
+            spawn_reader(next_id, reader, tx.clone());
+            let outbox = spawn_writer(writer);
+            if world.spellings.is_empty() {
+                word = choose(&words);
+                world.restart(vec![word.clone()]);
+            }
+            let player = world.join();
+            let c = Client { id: next_id, stream, outbox, player, ramp: Ramp::default(), action: None, point: None };
+            send(&c, &format!("WELCOME {} {:.0} {:.0}", player, world.size.x, world.size.y));
+            send(&c, &level_line);
+            send(&c, &format!("WORD {}", word));
+            println!("{} joined as player {}", peer, player + 1);
+            clients.push(c);
+            next_id += 1;
+        }
+
+        // input and farewells
+        while let Ok((id, line)) = rx.try_recv() {
+            let Some(k) = clients.iter().position(|c| c.id == id) else { continue };
+            match line.as_deref().and_then(ClientMsg::parse) {
+                Some(ClientMsg::Input(a)) => {
+                    clients[k].action = a;
+                    clients[k].point = None;
+                }
+                Some(ClientMsg::Point(p)) => clients[k].point = Some(p),
+                Some(ClientMsg::Ramp(r)) => clients[k].ramp = r,
+                None if line.is_none() => {
+                    let gone = clients.remove(k);
+                    world.leave(gone.player);
+                    for c in &mut clients {
+                        if c.player > gone.player {
+                            c.player -= 1;
+                        }
+                    }
+                    println!("player {} left", gone.player + 1);
+                }
+                None => {}
+            }
+        }
+
+        if clients.is_empty() {
+            world.spellings.clear();
+            thread::sleep(FRAME);
+            continue;
+        }
+
+        if pause > 0 {
+            pause -= 1;
+            if pause == 0 {
+                word = choose(&words);
+                world.restart(vec![word.clone()]);
+                broadcast(&clients, &format!("WORD {}", word));
+            }
+        } else {
+            let mut forces = vec![vec2(0.0, 0.0); world.players.len()];
+            for c in &clients {
+                forces[c.player] = match c.point {
+                    Some(p) => world.players[c.player].steer_toward(p),
+                    None => world.players[c.player].action_force(c.action, &c.ramp),
+                };
+            }
+            world.step(&forces);
+
+            for event in std::mem::take(&mut world.events) {
+                match event {
+                    Event::Caught { letter } => broadcast(&clients, &format!("EVENT CAUGHT {}", letter)),
+                    Event::Stolen => broadcast(&clients, "EVENT STOLEN"),
+                    Event::Dropped => broadcast(&clients, "EVENT DROPPED"),
+                    Event::PowerUp { power, .. } => broadcast(&clients, &format!("EVENT POWER {}", power.name())),
+                    Event::WordDone { .. } => {
+                        broadcast(&clients, &format!("OVER {}", result(&world, None)));
+                        pause = ROUND_PAUSE;
+                        break;
+                    }
+                    Event::Lost { player } => {
+                        broadcast(&clients, &format!("OVER {}", result(&world, Some(player))));
+                        pause = ROUND_PAUSE;
+                        break;
+                    }
+                }
+            }
+        }
+
+        if frame.is_multiple_of(2) {
+            let line = net::state_line(&world);
+            broadcast(&clients, &line);
+        }
+        frame += 1;
+
+        if let Some(rest) = FRAME.checked_sub(t0.elapsed()) {
+            thread::sleep(rest);
+        }
+    }
+}
+
+fn spawn_reader(id: usize, stream: TcpStream, tx: Sender<Incoming>) {
+    thread::spawn(move|| {
+        for line in BufReader::new(stream).lines() {
+            let Ok(line) = line else { break };
+            if tx.send((id, Some(line))).is_err() {
+                return;
+            }
+        }
+        let _ = tx.send((id, None));
+    });
+}
+
+// every client has its own writer thread, so a slow one does not hold up
+// the others; it ends when the client is dropped or cannot be written to
+fn spawn_writer(mut stream: TcpStream) -> SyncSender<String> {
+    let (tx, rx) = mpsc::sync_channel::<String>(OUTBOX);
+    thread::spawn(move|| {
+        for line in rx {
+            if writeln!(stream, "{}", line).is_err() {
+                break;
+            }
+        }
+        let _ = stream.shutdown(Shutdown::Both);
+    });
+    tx
+}
+
+// a client that falls too far behind, or whose writer has stopped, is shut
+// out; its reader thread then sees the end and the main loop drops it
+fn send(c: &Client, line: &str) {
+    if c.outbox.try_send(line.to_owned()).is_err() {
+        let _ = c.stream.shutdown(Shutdown::Both);
+    }
+}
+
+fn broadcast(clients: &[Client], line: &str) {
+    for c in clients.iter() {
+        send(c, line);
+    }
+}
+
+fn choose(words: &[String]) -> String {
+    words[rand::thread_rng().gen_range(0..words.len())].clone()
+}
+
+fn result(world: &World, out: Option<usize>) -> String {
+    let best = (0..world.players.len())
+        .filter(|&i| Some(i) != out)
+        .max_by_key(|&i| world.players[i].score);
+    match (out, best) {
+        (Some(o), Some(b)) => format!("Player {} is out. Player {} wins!", o + 1, b + 1),
+        (Some(o), None) => format!("Player {} is out.", o + 1),
+        (None, Some(b)) => format!("Player {} wins with {} letters!", b + 1, world.players[b].score),
+        (None, None) => String::new(),
+    }
+}
@@ -0,0 +1,162 @@
+// A computer player. It presses the same four movement keys a person
+// does, so it has to live with the same inertia and springs. Skill sets
+// how fast it reacts, how well it aims and how often it goes for the
+// wrong letter.
+
+use rand::Rng;
+
+use egui::Vec2;
+
+use crate::controls::{Action, Ramp};
+use crate::world::World;
+
+use crate::consts::{
+    DIAMETER,
+    PREDATORS,
+    ang_diff,
+};
+
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum Skill {
+    Easy,
+    Normal,
+    Hard,
+}
+
+pub const SKILLS: [Skill; 3] = [Skill::Easy, Skill::Normal, Skill::Hard];
+
+impl Skill {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Skill::Easy   => "Easy",
+            Skill::Normal => "Normal",
+            Skill::Hard   => "Hard",
+        }
+    }
+
+    // the free foods of a level with this difficulty
+    pub fn predators(&self) -> usize {
+        match self {
+            Skill::Easy   => 1,
+            Skill::Normal => PREDATORS,
+            Skill::Hard   => PREDATORS + 2,
+        }
+    }
+
+    // frames between two decisions
+    fn reaction(&self) -> u32 {
+        match self {
+            Skill::Easy   => 30,
+            Skill::Normal => 12,
+            Skill::Hard   => 4,
+        }
+    }
+
+    // how far off the heading may be before it turns, in radians
+    fn aim(&self) -> f32 {
+        match self {
+            Skill::Easy   => 0.6,
+            Skill::Normal => 0.35,
+            Skill::Hard   => 0.15,
+        }
+    }
+
+    // chance of picking a random letter instead of the right one
+    fn mistakes(&self) -> f64 {
+        match self {
+            Skill::Easy   => 0.3,
+            Skill::Normal => 0.1,
+            Skill::Hard   => 0.0,
+        }
+    }
+
+    // share of the full forward force it uses
+    fn speed(&self) -> f32 {
+        match self {
+            Skill::Easy   => 0.5,
+            Skill::Normal => 0.8,
+            Skill::Hard   => 1.0,
+        }
+    }
+}
+
+
+pub struct Ai {
+    pub skill: Skill,
+    action:    Option<Action>,
+    wait:      u32,
+    target:    Option<usize>, // food id
+}
+
+impl Ai {
+    pub fn new(skill: Skill) -> Self {
+        Self {
+            skill,
+            action: None,
+            wait:   0,
+            target: None,
+        }
+    }
+
+    pub fn ramp(&self) -> Ramp {
+        let mut ramp = Ramp::default();
+        ramp.max_forward *= self.skill.speed();
+        ramp
+    }
+
+    pub fn think(&mut self, world: &World, me: usize) -> Option<Action> {
+        if self.wait > 0 {
+            self.wait -= 1;
+            return self.action;
+        }
+        self.wait = self.skill.reaction();
+
+        let player = &world.players[me];
+        let owner = player.spelling;
+        let Some(next) = world.spellings[owner].next_letter() else {
+            self.action = None;
+            return None;
+        };
+        let head = player.worm.head.position;
+
+        // keep a target while it still carries the right letter
+        let still_good = self.target
+            .and_then(|id| world.foods.iter().find(|f| f.id == id))
+            .is_some_and(|f| f.owner == owner && f.tag == Some(next));
+        if !still_good {
+            self.target = self.pick(world, owner, next, head);
+        }
+        let Some(target) = self.target.and_then(|id| world.foods.iter().find(|f| f.id == id)) else {
+            self.action = Some(Action::Brake);
+            return self.action;
+        };
+
+        let seg: Vec2 = world.delta(target.tag_pos, head);
+        let heading = world.delta(head, player.worm.neck.position).angle();
+        let diff = ang_diff(seg.angle(), heading);
+
+        self.action = if seg.length() < 3.0 * DIAMETER && player.worm.head.velocity.length() > 4.0 {
+            Some(Action::Brake)
+        } else if diff > self.skill.aim() {
+            Some(Action::Right)
+        } else if diff < -self.skill.aim() {
+            Some(Action::Left)
+        } else {
+            Some(Action::Forward)
+        };
+        self.action
+    }
+
+    fn pick(&self, world: &World, owner: usize, next: char, head: Vec2) -> Option<usize> {
+        let mut rng = rand::thread_rng();
+        let letters: Vec<_> = world.foods.iter().filter(|f| f.tag.is_some() && f.owner == owner).collect();
+        if rng.gen_bool(self.skill.mistakes()) && !letters.is_empty() {
+            return Some(letters[rng.gen_range(0..letters.len())].id);
+        }
+        letters.into_iter()
+            .filter(|f| f.tag == Some(next))
+            .min_by(|a, b| world.delta(a.tag_pos, head).length().total_cmp(&world.delta(b.tag_pos, head).length()))
+            .map(|f| f.id)
+    }
+}
@@ -0,0 +1,67 @@
+use std::thread;
+use std::path::PathBuf;
+
+use ears::{
+    Sound,
+    AudioController
+};
+
+// libsndfile (behind ears) decodes all of these
+pub const AUDIO_EXTENSIONS: [&str; 3] = ["ogg", "flac", "wav"];
+
+
+const SOUNDS: &str = "sounds";
+
+
+pub struct Audio {
+    dirs: Vec<PathBuf>,
+}
+
+impl Default for Audio {
+    fn default() -> Self {
+        Self {
+            dirs: vec![PathBuf::from(SOUNDS)],
+        }
+    }
+}
+
+impl Audio {
+    // the sounds of the pack in use, if any, searched before the built-in
+    // ones so that it can override them; those of an earlier pack are gone
+    pub fn use_pack(&mut self, dir: Option<PathBuf>) {
+        self.dirs = vec![PathBuf::from(SOUNDS)];
+        self.dirs.extend(dir);
+    }
+
+    pub fn find(&self, name: &str) -> Option<PathBuf> {
+        for dir in self.dirs.iter().rev() {
+            for ext in AUDIO_EXTENSIONS {
+                let path = dir.join(format!("{}.{}", name, ext));
+                if path.is_file() {
+                    return Some(path);
+                }
+            }
+        }
+        None
+    }
+
+    pub fn play(&self, name: &str) {
+        self.play_all(vec![name.to_owned()]);
+    }
+
+    // plays the sounds one after another, skipping missing files
+    pub fn play_all(&self, names: Vec<String>) {
+        let paths: Vec<PathBuf> = names.iter().filter_map(|n| self.find(n)).collect();
+        if paths.is_empty() {
+            return;
+        }
+        thread::spawn(move|| {
+            for path in paths {
+                if let Ok(mut snd) = Sound::new(&path.to_string_lossy()) {
+                    snd.play();
+                    while snd.is_playing() {}
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,229 @@
+// How the units of a worm follow its neck. Each body model is one way of
+// doing that; the toggle key cycles a worm through them, and their numbers
+// can be tuned while playing.
+
+use std::ops::RangeInclusive;
+
+use egui::Vec2;
+
+use crate::unit::Unit;
+use crate::consts::{
+    MS_UNIT,
+    KS_UNIT,
+    KF_UNIT,
+    L0_UNIT,
+    calc_hooke_force,
+    ang_diff,
+};
+
+
+pub trait BodyModel {
+    fn name(&self) -> &'static str;
+
+    // one substep of the units following the neck
+    fn follow(&mut self, neck: Vec2, units: &mut [Unit], dt: f32);
+
+    // the numbers that can be tuned, with sensible ranges
+    fn params(&mut self) -> Vec<Param<'_>>;
+}
+
+pub struct Param<'a> {
+    pub name:  &'static str,
+    pub value: &'a mut f32,
+    pub range: RangeInclusive<f32>,
+}
+
+fn param<'a>(name: &'static str, value: &'a mut f32, range: RangeInclusive<f32>) -> Param<'a> {
+    Param { name, value, range }
+}
+
+// one of each, in toggle order; the first is where worms start
+pub fn models() -> Vec<Box<dyn BodyModel>> {
+    vec![
+        Box::new(Springs::default()),
+        Box::new(Follow::default()),
+        Box::new(Rope::default()),
+        Box::new(Snake::default()),
+    ]
+}
+
+
+// each unit hangs on a Hooke spring from the one ahead
+pub struct Springs {
+    ks: f32,
+    kf: f32,
+    ms: f32,
+}
+
+impl Default for Springs {
+    fn default() -> Self {
+        Self { ks: KS_UNIT, kf: KF_UNIT, ms: MS_UNIT }
+    }
+}
+
+impl BodyModel for Springs {
+    fn name(&self) -> &'static str {
+        "Springs"
+    }
+
+    fn follow(&mut self, neck: Vec2, units: &mut [Unit], dt: f32) {
+        let mut p_lead = neck;
+        for u in units {
+            let f = calc_hooke_force(p_lead - u.position, u.l0, self.ks);
+            u.velocity += dt * (f - self.kf * u.velocity) / self.ms;
+            u.position += dt * u.velocity;
+            p_lead = u.position;
+        }
+    }
+
+    fn params(&mut self) -> Vec<Param<'_>> {
+        vec![
+            param("stiffness", &mut self.ks, 0.005..=0.2),
+            param("friction",  &mut self.kf, 0.01..=0.5),
+            param("mass",      &mut self.ms, 0.05..=1.0),
+        ]
+    }
+}
+
+
+// follow the leader: each unit is pulled to a point one link behind the
+// unit ahead, in the direction that unit is heading
+pub struct Follow {
+    ks: f32,
+    kf: f32,
+    ms: f32,
+}
+
+impl Default for Follow {
+    fn default() -> Self {
+        Self { ks: KS_UNIT, kf: KF_UNIT, ms: MS_UNIT }
+    }
+}
+
+impl BodyModel for Follow {
+    fn name(&self) -> &'static str {
+        "Follow"
+    }
+
+    fn follow(&mut self, neck: Vec2, units: &mut [Unit], dt: f32) {
+        let Some(first) = units.first() else { return };
+        let ang = (neck - first.position).angle();
+        let mut p_target = neck - L0_UNIT * Vec2::angled(ang);
+        let mut p_lead = neck;
+        for u in units {
+            let f = self.ks * (p_target - u.position);
+            u.velocity += dt * (f - self.kf * u.velocity) / self.ms;
+            u.position += dt * u.velocity;
+            let ang = (p_lead - u.position).angle(); // this is the key
+            p_target = u.position - u.l0 * Vec2::angled(ang);
+            p_lead = u.position;
+        }
+    }
+
+    fn params(&mut self) -> Vec<Param<'_>> {
+        vec![
+            param("stiffness", &mut self.ks, 0.005..=0.2),
+            param("friction",  &mut self.kf, 0.01..=0.5),
+            param("mass",      &mut self.ms, 0.05..=1.0),
+        ]
+    }
+}
+
+
+// a rope: the units drift on their own and links of fixed length pull
+// them along, both ways, a few times per substep
+pub struct Rope {
+    damping:    f32, // speed kept per frame
+    iterations: f32,
+}
+
+impl Default for Rope {
+    fn default() -> Self {
+        Self { damping: 0.9, iterations: 4.0 }
+    }
+}
+
+impl BodyModel for Rope {
+    fn name(&self) -> &'static str {
+        "Rope"
+    }
+
+    fn follow(&mut self, neck: Vec2, units: &mut [Unit], dt: f32) {
+        if units.is_empty() {
+            return;
+        }
+        let before: Vec<Vec2> = units.iter().map(|u| u.position).collect();
+        let keep = self.damping.powf(dt);
+        for u in units.iter_mut() {
+            u.velocity *= keep;
+            u.position += dt * u.velocity;
+        }
+        for _ in 0..self.iterations.round() as usize {
+            // the neck does not give
+            let d = units[0].position - neck;
+            if d.length() > 0.0 {
+                units[0].position = neck + d * (units[0].l0 / d.length());
+            }
+            for i in 1..units.len() {
+                let d = units[i].position - units[i-1].position;
+                let len = d.length();
+                if len == 0.0 {
+                    continue;
+                }
+                let half = 0.5 * (len - units[i].l0) * d / len;
+                units[i-1].position += half;
+                units[i].position   -= half;
+            }
+        }
+        for (u, p) in units.iter_mut().zip(before) {
+            u.velocity = (u.position - p) / dt;
+        }
+    }
+
+    fn params(&mut self) -> Vec<Param<'_>> {
+        vec![
+            param("damping",    &mut self.damping, 0.5..=1.0),
+            param("iterations", &mut self.iterations, 1.0..=12.0),
+        ]
+    }
+}
+
+
+// inverse kinematics: each unit sits exactly one link behind the unit
+// ahead, and a joint bends no more than a set angle
+pub struct Snake {
+    max_bend: f32, // radians per joint
+}
+
+impl Default for Snake {
+    fn default() -> Self {
+        Self { max_bend: 0.6 }
+    }
+}
+
+impl BodyModel for Snake {
+    fn name(&self) -> &'static str {
+        "Snake"
+    }
+
+    fn follow(&mut self, neck: Vec2, units: &mut [Unit], dt: f32) {
+        let mut p_lead = neck;
+        let mut heading: Option<f32> = None; // of the link ahead
+        for u in units {
+            let mut ang = (p_lead - u.position).angle();
+            if let Some(h) = heading {
+                let bend = ang_diff(ang, h).clamp(-self.max_bend, self.max_bend);
+                ang = h + bend;
+            }
+            let pos = p_lead - u.l0 * Vec2::angled(ang);
+            u.velocity = (pos - u.position) / dt;
+            u.position = pos;
+            heading = Some(ang);
+            p_lead = pos;
+        }
+    }
+
+    fn params(&mut self) -> Vec<Param<'_>> {
+        vec![param("max bend", &mut self.max_bend, 0.1..=1.5)]
+    }
+}
@@ -0,0 +1,41 @@
+// The part of the world on screen. It eases toward a target, usually the
+// head of the worm, and stops at the edges of the world.
+
+use egui::Vec2;
+
+const EASE: f32 = 0.08; // share of the way to the target covered per frame
+
+
+#[derive(Default)]
+pub struct Camera {
+    pub center: Vec2,
+}
+
+impl Camera {
+
+    pub fn follow(&mut self, target: Vec2, view: Vec2, world: Vec2) {
+        // a worm that wrapped around to the other side is not chased across
+        let d = target - self.center;
+        if d.x.abs() > 0.5 * world.x || d.y.abs() > 0.5 * world.y {
+            self.jump(target, view, world);
+            return;
+        }
+        self.center += d * EASE;
+        self.center = clamp(self.center, view, world);
+    }
+
+    pub fn jump(&mut self, target: Vec2, view: Vec2, world: Vec2) {
+        self.center = clamp(target, view, world);
+    }
+
+    // the world position at the top left corner of the screen
+    pub fn offset(&self, view: Vec2) -> Vec2 {
+        self.center - 0.5 * view
+    }
+}
+
+// a world smaller than the screen stays in the middle
+fn clamp(center: Vec2, view: Vec2, world: Vec2) -> Vec2 {
+    let axis = |c: f32, v: f32, w: f32| if w <= v { 0.5 * w } else { c.clamp(0.5 * v, w - 0.5 * v) };
+    Vec2::new(axis(center.x, view.x, world.x), axis(center.y, view.y, world.y))
+}
@@ -9,12 +9,104 @@ use egui::{
 };
 
 
-#[derive(PartialEq)]
-pub enum GameState {
-    StartUI,
-    GameOverUI,
-    Init,
+// The screens of the game. They stack: pause and results sit on top of
+// the game, the controls on top of whatever opened them.
+#[derive(PartialEq, Clone, Copy)]
+pub enum Screen {
+    Menu,
+    Controls,
+    Editor,
     Play,
+    Pause,
+    Results,
+}
+
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum PlayMode {
+    Solo,
+    SharedWord, // two players race for the letters of one word
+    OwnWords,   // two players, each spells their own word
+    VsComputer, // one player against a computer worm, one word
+}
+
+impl PlayMode {
+    pub fn label(&self) -> &'static str {
+        match self {
+            PlayMode::Solo       => "One player",
+            PlayMode::SharedWord => "Two players, one word",
+            PlayMode::OwnWords   => "Two players, own words",
+            PlayMode::VsComputer => "Against the computer",
+        }
+    }
+
+    pub fn n_players(&self) -> usize {
+        match self {
+            PlayMode::Solo => 1,
+            _ => 2,
+        }
+    }
+
+    pub fn n_words(&self) -> usize {
+        match self {
+            PlayMode::OwnWords => 2,
+            _ => 1,
+        }
+    }
+}
+
+pub const PLAY_MODES: [PlayMode; 4] = [
+    PlayMode::Solo,
+    PlayMode::SharedWord,
+    PlayMode::OwnWords,
+    PlayMode::VsComputer,
+];
+
+
+// how much larger than the window the world is
+#[derive(PartialEq, Clone, Copy)]
+pub enum ArenaSize {
+    Window,
+    Large,
+    Huge,
+}
+
+impl ArenaSize {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ArenaSize::Window => "Window",
+            ArenaSize::Large  => "Large",
+            ArenaSize::Huge   => "Huge",
+        }
+    }
+
+    pub fn scale(&self) -> f32 {
+        match self {
+            ArenaSize::Window => 1.0,
+            ArenaSize::Large  => 2.0,
+            ArenaSize::Huge   => 3.0,
+        }
+    }
+}
+
+pub const ARENA_SIZES: [ArenaSize; 3] = [ArenaSize::Window, ArenaSize::Large, ArenaSize::Huge];
+
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum LetterAudio {
+    Off,
+    Name,  // "bee"
+    Sound, // "/b/"
+}
+
+impl LetterAudio {
+    pub fn file(&self, letter: char) -> Option<String> {
+        match self {
+            LetterAudio::Off   => None,
+            LetterAudio::Name  => Some(format!("letters/name/{}", letter)),
+            LetterAudio::Sound => Some(format!("letters/sound/{}", letter)),
+        }
+    }
 }
 
 
@@ -85,6 +177,20 @@ pub const SPORT_AND_GAMES: [&str; 12] = [
   "volleyball", "weightlifting"
 ];
 
+// the built-in vocabularies: name, title recording, words
+pub const VOCABS: [(&str, &str, &[&str]); 10] = [
+  ("Zoo Animals",     "zoo-animals",     &ZOO_ANIMALS),
+  ("Sea Animals",     "sea-animals",     &SEA_ANIMALS),
+  ("Birds",           "birds",           &BIRDS),
+  ("Insects",         "insects",         &INSECTS),
+  ("Farm Animals",    "farm-animals",    &FARM_ANIMALS),
+  ("Body Parts",      "body-parts",      &BODY_PARTS),
+  ("Fruits",          "fruits",          &FRUITS),
+  ("Vegetables",      "vegetables",      &VEGETABLES),
+  ("Food and Drinks", "food-and-drinks", &FOOD_AND_DRINKS),
+  ("Sport and Games", "sport-and-games", &SPORT_AND_GAMES),
+];
+
 pub const HEAD_SIZE:   f32 = 28.0;
 pub const HALF_SIZE:   f32 = 0.5 * HEAD_SIZE;
 pub const EYE_SIZE:    f32 = 0.25 * HEAD_SIZE;
@@ -92,15 +198,41 @@ pub const FOOD_LEN:    f32 = 0.8 * HEAD_SIZE;
 
 pub const FOOD_SPEED:  f32 = 0.8;
 pub const AVOID_RATE:  f32 = 0.01;
+pub const WATER_DRAG:  f32 = 0.96; // head speed kept per frame in water
+pub const WATER_SLOW:  f32 = 0.5;  // food speed in water
 
+pub const INIT_X:      f32 = 90.0;
 pub const INIT_Y:      f32 = 130.0;
+pub const PLAYER_GAP:  f32 = 100.0; // between the start lines of the players
+
+pub const LETTERS_SHOWN: usize = 3; // letters of a word on screen at once
+pub const PREDATORS:     usize = 2;
+pub const STEAL_COOLDOWN: u32  = 60; // frames before a worm can steal again
+pub const SELF_SKIP:     usize = 3;  // own units too close to the head to bump into
 
 pub const MAX_FORWARD: f32 = 0.17;
 pub const MAX_TURN:    f32 = 1.20;
 
+pub const POINTER_GAIN:  f32 = 0.05;  // desired speed per pixel to the pointer
+pub const POINTER_SPEED: f32 = 6.0;   // top speed when following the pointer
+pub const POINTER_FORCE: f32 = 0.30;
+
+pub const ANALOG_GRIP:   f32 = 0.30;  // force against sideways speed
+
+// each frame is integrated in a few smaller steps, dt is in frames of 1/60 s;
+// a slow frame gets more of them, up to MAX_FRAMES frames' worth
+pub const SUBSTEPS:    usize = 4;
+pub const DT:          f32 = 1.0 / SUBSTEPS as f32;
+pub const MAX_FRAMES:  f32 = 4.0; // below 15 fps the game slows down instead
+
+pub const MS_LEAD:     f32 = 3.9;
 pub const KF_LEAD:     f32 = 0.02;
 pub const L0_LEAD:     f32 = 10.0;
 
+pub const MS_NECK:     f32 = 0.10;
+pub const KF_NECK:     f32 = 0.15;
+pub const KS_NECK:     f32 = 0.06;
+
 pub const R_LEAD:      f32 = 18.0;
 pub const R_UNIT:      f32 = 13.0;
 pub const R_TAIL:      f32 = 12.0;
@@ -116,6 +248,29 @@ pub const DIAMETER:    f32 = 2.0 * R_UNIT;
 pub const FONT_ID: FontId  = FontId{size: 15., family: FontFamily::Proportional};
 
 pub const PURPLE1: Color32   = Color32::from_rgb(190, 52, 229);
+pub const PURPLE2: Color32   = Color32::from_rgb(190, 79, 233);
+pub const PURPLE3: Color32   = Color32::from_rgb(208,106, 230);
+pub const ORANGE1: Color32   = Color32::from_rgb(230, 126, 34);
+pub const ORANGE2: Color32   = Color32::from_rgb(235, 152, 78);
+pub const ORANGE3: Color32   = Color32::from_rgb(240, 178, 122);
+
+pub const TEAL1:   Color32   = Color32::from_rgb(22, 160, 133);
+pub const TEAL2:   Color32   = Color32::from_rgb(72, 185, 163);
+pub const TEAL3:   Color32   = Color32::from_rgb(122, 205, 189);
+pub const BLUE1:   Color32   = Color32::from_rgb(41, 128, 185);
+pub const BLUE2:   Color32   = Color32::from_rgb(84, 153, 199);
+pub const BLUE3:   Color32   = Color32::from_rgb(127, 179, 213);
+
+// head, first and second unit of each player
+pub const PALETTES: [[Color32; 3]; 4] = [
+    [PURPLE1, PURPLE2, PURPLE3],
+    [ORANGE1, ORANGE2, ORANGE3],
+    [TEAL1,   TEAL2,   TEAL3],
+    [BLUE1,   BLUE2,   BLUE3],
+];
+
+// letters of the first and the second word
+pub const LETTER_COLORS: [Color32; 2] = [Color32::DARK_RED, Color32::DARK_BLUE];
 
 
 pub const BODY: [Vec2; 4] = [
@@ -158,3 +313,13 @@ pub fn ang_diff(a: f32, b: f32) -> f32 {
     result
 }
 
+// the shortest way across an arena that wraps around
+pub fn wrap_delta(d: Vec2, size: Vec2) -> Vec2 {
+    let axis = |d: f32, s: f32| if s > 0.0 { d - s * (d / s).round() } else { d };
+    vec2(axis(d.x, size.x), axis(d.y, size.y))
+}
+
+pub fn wrap_pos(p: Vec2, size: Vec2) -> Vec2 {
+    let axis = |p: f32, s: f32| if s > 0.0 { p.rem_euclid(s) } else { p };
+    vec2(axis(p.x, size.x), axis(p.y, size.y))
+}
@@ -0,0 +1,327 @@
+use std::fs;
+use std::collections::HashSet;
+
+use egui::Key;
+
+use crate::consts::{
+    MAX_FORWARD,
+    MAX_TURN,
+};
+
+pub const CONTROLS_FILE: &str = "controls.cfg";
+
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum Action {
+    Forward,
+    Left,
+    Right,
+    Brake,
+    Pause,
+    PlayAudio,
+    Reset,
+    Toggle,
+    Tuning,
+    Debug,
+    Quit,
+}
+
+pub const ACTIONS: [Action; 11] = [
+    Action::Forward,
+    Action::Left,
+    Action::Right,
+    Action::Brake,
+    Action::Pause,
+    Action::PlayAudio,
+    Action::Reset,
+    Action::Toggle,
+    Action::Tuning,
+    Action::Debug,
+    Action::Quit,
+];
+
+impl Action {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::Forward   => "forward",
+            Action::Left      => "left",
+            Action::Right     => "right",
+            Action::Brake     => "brake",
+            Action::Pause     => "pause",
+            Action::PlayAudio => "play audio",
+            Action::Reset     => "reset",
+            Action::Toggle    => "toggle",
+            Action::Tuning    => "tuning",
+            Action::Debug     => "debug",
+            Action::Quit      => "quit",
+        }
+    }
+
+    fn cfg_name(&self) -> &'static str {
+        match self {
+            Action::PlayAudio => "play_audio",
+            _ => self.label(),
+        }
+    }
+}
+
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum Scheme {
+    Arrows,
+    Wasd,
+    OneHanded, // everything within reach of the left hand
+    Custom,
+}
+
+impl Scheme {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Scheme::Arrows    => "Arrow keys",
+            Scheme::Wasd      => "WASD",
+            Scheme::OneHanded => "One-handed",
+            Scheme::Custom    => "Custom",
+        }
+    }
+
+    fn cfg_name(&self) -> &'static str {
+        match self {
+            Scheme::Arrows    => "arrows",
+            Scheme::Wasd      => "wasd",
+            Scheme::OneHanded => "one-handed",
+            Scheme::Custom    => "custom",
+        }
+    }
+
+    // in the order of ACTIONS
+    fn keys(&self) -> [Key; 11] {
+        match self {
+            Scheme::Arrows | Scheme::Custom => [
+                Key::ArrowUp, Key::ArrowLeft, Key::ArrowRight, Key::ArrowDown,
+                Key::Space, Key::P, Key::R, Key::F1, Key::F2, Key::F3, Key::Escape,
+            ],
+            Scheme::Wasd => [
+                Key::W, Key::A, Key::D, Key::S,
+                Key::Space, Key::P, Key::R, Key::F1, Key::F2, Key::F3, Key::Escape,
+            ],
+            Scheme::OneHanded => [
+                Key::W, Key::A, Key::D, Key::S,
+                Key::Space, Key::Q, Key::R, Key::E, Key::F2, Key::F3, Key::Escape,
+            ],
+        }
+    }
+}
+
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum SteerMode {
+    Keys,
+    Analog,  // thrust and turn together, the heading turns smoothly
+    Pointer, // the head follows the mouse or a finger on a touchscreen
+    Typing,  // the worm heads for the food with the letter typed
+}
+
+pub const STEER_MODES: [SteerMode; 4] = [SteerMode::Keys, SteerMode::Analog, SteerMode::Pointer, SteerMode::Typing];
+
+impl SteerMode {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SteerMode::Keys    => "Keyboard",
+            SteerMode::Analog  => "Analog keys",
+            SteerMode::Pointer => "Mouse / touch",
+            SteerMode::Typing  => "Typing",
+        }
+    }
+
+    fn cfg_name(&self) -> &'static str {
+        match self {
+            SteerMode::Keys    => "keys",
+            SteerMode::Analog  => "analog",
+            SteerMode::Pointer => "pointer",
+            SteerMode::Typing  => "typing",
+        }
+    }
+}
+
+
+// How the held movement keys build up force. The ramp values accumulate
+// by a step per frame up to a maximum; release_frames keeps a key "held"
+// for a few frames after it is let go, so a shaky hand does not reset it.
+// Analog steering turns the heading by up to max_spin radians a frame;
+// curve shapes how thrust and spin build up (1 is linear).
+#[derive(Clone, Copy)]
+pub struct Ramp {
+    pub forward_base: f32,
+    pub forward_step: f32,
+    pub max_forward:  f32,
+    pub turn_base:    f32,
+    pub turn_step:    f32,
+    pub max_turn:     f32,
+    pub release_frames: u32,
+    pub spin_step:    f32,
+    pub max_spin:     f32,
+    pub curve:        f32,
+}
+
+impl Default for Ramp {
+    fn default() -> Self {
+        Self {
+            forward_base: 0.05,
+            forward_step: 0.01,
+            max_forward:  MAX_FORWARD,
+            turn_base:    0.2,
+            turn_step:    0.01,
+            max_turn:     MAX_TURN,
+            release_frames: 0,
+            spin_step:    0.05,
+            max_spin:     0.06,
+            curve:        2.0,
+        }
+    }
+}
+
+
+#[derive(Clone)]
+pub struct Controls {
+    pub steer:  SteerMode,
+    pub scheme: Scheme,
+    pub ramp:   Ramp,
+    keys:       [Key; 11],
+}
+
+impl Default for Controls {
+    fn default() -> Self {
+        Self::from_scheme(Scheme::Arrows)
+    }
+}
+
+impl Controls {
+    pub fn from_scheme(scheme: Scheme) -> Self {
+        Self {
+            steer: SteerMode::Keys,
+            scheme,
+            ramp: Ramp::default(),
+            keys: scheme.keys(),
+        }
+    }
+
+    pub fn set_scheme(&mut self, scheme: Scheme) {
+        self.scheme = scheme;
+        if scheme != Scheme::Custom {
+            self.keys = scheme.keys();
+        }
+    }
+
+    pub fn key(&self, action: Action) -> Key {
+        self.keys[action as usize]
+    }
+
+    // a key can only do one thing, so whatever had it before gets the old key
+    pub fn set_key(&mut self, action: Action, key: Key) {
+        let old = self.keys[action as usize];
+        for k in &mut self.keys {
+            if *k == key {
+                *k = old;
+            }
+        }
+        self.keys[action as usize] = key;
+        self.scheme = Scheme::Custom;
+    }
+
+    pub fn down(&self, keys_down: &HashSet<Key>, action: Action) -> bool {
+        keys_down.contains(&self.key(action))
+    }
+
+    // the movement key held, the first one wins
+    pub fn movement(&self, keys_down: &HashSet<Key>) -> Option<Action> {
+        [Action::Forward, Action::Right, Action::Left, Action::Brake]
+            .into_iter()
+            .find(|&a| self.down(keys_down, a))
+    }
+
+    pub fn pressed(&self, ctx: &egui::Context, action: Action) -> bool {
+        let key = self.key(action);
+        if self.steer == SteerMode::Typing && is_letter(key) {
+            return false; // the letters are for typing
+        }
+        ctx.input(|i| i.key_pressed(key))
+    }
+
+    pub fn load() -> Self {
+        let mut controls = Self::default();
+        let Ok(text) = fs::read_to_string(CONTROLS_FILE) else { return controls };
+        for line in text.lines() {
+            let Some((name, value)) = line.split_once('=') else { continue };
+            let (name, value) = (name.trim(), value.trim());
+            if name == "steer" {
+                for m in STEER_MODES {
+                    if m.cfg_name() == value {
+                        controls.steer = m;
+                    }
+                }
+                continue;
+            }
+            if name == "scheme" {
+                for s in [Scheme::Arrows, Scheme::Wasd, Scheme::OneHanded, Scheme::Custom] {
+                    if s.cfg_name() == value {
+                        controls.set_scheme(s);
+                    }
+                }
+                continue;
+            }
+            if let Some(&a) = ACTIONS.iter().find(|a| a.cfg_name() == name) {
+                if let Some(key) = Key::from_name(value) {
+                    // swapped as in the menu, so that no key does two things;
+                    // the keys of a scheme are saved too, it stays that scheme
+                    let scheme = controls.scheme;
+                    controls.set_key(a, key);
+                    controls.scheme = scheme;
+                }
+                continue;
+            }
+            let r = &mut controls.ramp;
+            match name {
+                "forward_base"   => r.forward_base = value.parse().unwrap_or(r.forward_base),
+                "forward_step"   => r.forward_step = value.parse().unwrap_or(r.forward_step),
+                "max_forward"    => r.max_forward  = value.parse().unwrap_or(r.max_forward),
+                "turn_base"      => r.turn_base    = value.parse().unwrap_or(r.turn_base),
+                "turn_step"      => r.turn_step    = value.parse().unwrap_or(r.turn_step),
+                "max_turn"       => r.max_turn     = value.parse().unwrap_or(r.max_turn),
+                "release_frames" => r.release_frames = value.parse().unwrap_or(r.release_frames),
+                "spin_step"      => r.spin_step    = value.parse().unwrap_or(r.spin_step),
+                "max_spin"       => r.max_spin     = value.parse().unwrap_or(r.max_spin),
+                "curve"          => r.curve        = value.parse().unwrap_or(r.curve),
+                _ => {}
+            }
+        }
+        controls
+    }
+
+    pub fn save(&self) {
+        let mut out = format!("steer = {}\n", self.steer.cfg_name());
+        out += &format!("scheme = {}\n", self.scheme.cfg_name());
+        for a in ACTIONS {
+            out += &format!("{} = {}\n", a.cfg_name(), self.key(a).name());
+        }
+        let r = &self.ramp;
+        out += &format!("forward_base = {}\n", r.forward_base);
+        out += &format!("forward_step = {}\n", r.forward_step);
+        out += &format!("max_forward = {}\n", r.max_forward);
+        out += &format!("turn_base = {}\n", r.turn_base);
+        out += &format!("turn_step = {}\n", r.turn_step);
+        out += &format!("max_turn = {}\n", r.max_turn);
+        out += &format!("release_frames = {}\n", r.release_frames);
+        out += &format!("spin_step = {}\n", r.spin_step);
+        out += &format!("max_spin = {}\n", r.max_spin);
+        out += &format!("curve = {}\n", r.curve);
+        if let Err(e) = fs::write(CONTROLS_FILE, out) {
+            eprintln!("cannot save {}: {}", CONTROLS_FILE, e);
+        }
+    }
+}
+
+
+fn is_letter(key: Key) -> bool {
+    let name = key.name();
+    name.len() == 1 && name.chars().all(|c| c.is_ascii_alphabetic())
+}
@@ -0,0 +1,261 @@
+// How the foods move. A creature is a speed and a few steering behaviours,
+// each turning the food toward the way it wants to go at its own rate:
+//
+//   wander    drift about at random
+//   flee      run from the worm heads
+//   seek      head for the nearest part of a worm
+//   pursue    head for where that part will be
+//   hide      get a rock or wall between itself and a head
+//   flock     go along with the foods of the same kind
+//   separate  keep away from the other foods
+//
+// Letters and predators are played by a creature each, chosen per level.
+
+use rand::Rng;
+
+use egui::Vec2;
+
+use crate::world::{World, AVOID_RADIUS, FLEE_RADIUS, CHASE_RADIUS};
+use crate::consts::{
+    HEAD_SIZE,
+    DIAMETER,
+    AVOID_RATE,
+    ang_diff,
+};
+
+pub const LETTER_CREATURES:   [&str; 3] = ["bug", "timid", "grazer"];
+pub const PREDATOR_CREATURES: [&str; 3] = ["hunter", "fast", "grazer"];
+
+
+pub trait Behavior {
+    // the way the food at i would like to head, if it cares
+    fn steer(&self, world: &World, i: usize) -> Option<Vec2>;
+
+    // how far it looks, for the debug overlay
+    fn radius(&self) -> f32 {
+        0.0
+    }
+}
+
+
+pub struct Wander {
+    pub jitter: f32, // radians either way
+}
+
+impl Behavior for Wander {
+    fn steer(&self, world: &World, i: usize) -> Option<Vec2> {
+        let turn = rand::thread_rng().gen_range(-self.jitter..=self.jitter);
+        Some(Vec2::angled(world.foods[i].angle + turn))
+    }
+}
+
+
+// away from every head in reach, measured from the letter at the tail
+pub struct Flee {
+    pub radius: f32,
+}
+
+impl Behavior for Flee {
+    fn steer(&self, world: &World, i: usize) -> Option<Vec2> {
+        let from = world.foods[i].tag_pos;
+        let away: Vec2 = world.players.iter()
+            .map(|p| world.delta(from, p.worm.head.position))
+            .filter(|d| d.length() < self.radius)
+            .map(|d| d.normalized())
+            .fold(Vec2::ZERO, |a, b| a + b);
+        (away != Vec2::ZERO).then_some(away)
+    }
+
+    fn radius(&self) -> f32 {
+        self.radius
+    }
+}
+
+
+pub struct Seek {
+    pub radius: f32,
+}
+
+impl Behavior for Seek {
+    fn steer(&self, world: &World, i: usize) -> Option<Vec2> {
+        world.nearest_unit(world.foods[i].pos, self.radius).map(|u| u.2)
+    }
+
+    fn radius(&self) -> f32 {
+        self.radius
+    }
+}
+
+
+// like seek, but aims where the unit is going
+pub struct Pursue {
+    pub radius: f32,
+    pub ahead:  f32, // frames
+}
+
+impl Behavior for Pursue {
+    fn steer(&self, world: &World, i: usize) -> Option<Vec2> {
+        let (p, u, d) = world.nearest_unit(world.foods[i].pos, self.radius)?;
+        Some(d + self.ahead * world.players[p].worm.units[u].velocity)
+    }
+
+    fn radius(&self) -> f32 {
+        self.radius
+    }
+}
+
+
+// to the far side of the nearest rock or wall from the nearest head
+pub struct Hide {
+    pub radius: f32,
+}
+
+impl Behavior for Hide {
+    fn steer(&self, world: &World, i: usize) -> Option<Vec2> {
+        let pos = world.foods[i].pos;
+        let head = world.players.iter()
+            .map(|p| p.worm.head.position)
+            .min_by(|a, b| world.delta(pos, *a).length().total_cmp(&world.delta(pos, *b).length()))
+            .filter(|h| world.delta(pos, *h).length() < self.radius)?;
+        let (cover, size) = world.obstacles.iter()
+            .filter_map(|o| o.nearest_solid(pos))
+            .min_by(|a, b| (a.0 - pos).length().total_cmp(&(b.0 - pos).length()))?;
+        let behind = cover + (cover - head).normalized() * (size + DIAMETER);
+        Some(world.delta(behind, pos))
+    }
+
+    fn radius(&self) -> f32 {
+        self.radius
+    }
+}
+
+
+// the same heading as the foods of its kind nearby, and toward them
+pub struct Flock {
+    pub radius: f32,
+}
+
+impl Behavior for Flock {
+    fn steer(&self, world: &World, i: usize) -> Option<Vec2> {
+        let me = &world.foods[i];
+        let mut heading = Vec2::ZERO;
+        let mut center = Vec2::ZERO;
+        let mut n = 0;
+        for j in world.foods_near(i, self.radius) {
+            let fd = &world.foods[j];
+            let d = world.delta(fd.pos, me.pos);
+            if fd.tag.is_some() != me.tag.is_some() {
+                continue;
+            }
+            heading += Vec2::angled(fd.angle);
+            center += d;
+            n += 1;
+        }
+        if n == 0 {
+            return None;
+        }
+        Some(heading.normalized() + (center / n as f32).normalized())
+    }
+
+    fn radius(&self) -> f32 {
+        self.radius
+    }
+}
+
+
+pub struct Separate {
+    pub radius: f32,
+}
+
+impl Behavior for Separate {
+    fn steer(&self, world: &World, i: usize) -> Option<Vec2> {
+        let pos = world.foods[i].pos;
+        let away: Vec2 = world.foods_near(i, self.radius)
+            .map(|j| world.delta(pos, world.foods[j].pos))
+            .filter(|d| d.length() > 0.0)
+            .map(|d| d.normalized())
+            .fold(Vec2::ZERO, |a, b| a + b);
+        (away != Vec2::ZERO).then_some(away)
+    }
+}
+
+
+pub struct Creature {
+    pub speed: f32, // times FOOD_SPEED
+    behaviors: Vec<(f32, Box<dyn Behavior>)>, // turn rate and behaviour
+}
+
+impl Creature {
+
+    // letters that keep their distance, as they always have
+    pub fn bug() -> Self {
+        Self {
+            speed: 1.0,
+            behaviors: vec![
+                (AVOID_RATE, Box::new(Separate { radius: AVOID_RADIUS })),
+                (1.0 / 30.0, Box::new(Flee { radius: FLEE_RADIUS })),
+            ],
+        }
+    }
+
+    // predators that go for the nearest part of a worm
+    pub fn hunter() -> Self {
+        Self {
+            speed: 1.0,
+            behaviors: vec![
+                (AVOID_RATE, Box::new(Separate { radius: AVOID_RADIUS })),
+                (0.02, Box::new(Seek { radius: CHASE_RADIUS })),
+            ],
+        }
+    }
+
+    pub fn by_name(name: &str) -> Option<Self> {
+        let creature = match name {
+            "bug"    => Self::bug(),
+            "hunter" => Self::hunter(),
+            // quick letters that bolt early and hide behind rocks
+            "timid" => Self {
+                speed: 1.2,
+                behaviors: vec![
+                    (AVOID_RATE, Box::new(Separate { radius: AVOID_RADIUS })),
+                    (0.02, Box::new(Wander { jitter: 0.8 })),
+                    (0.06, Box::new(Flee { radius: 1.5 * FLEE_RADIUS })),
+                    (0.04, Box::new(Hide { radius: 2.0 * FLEE_RADIUS })),
+                ],
+            },
+            // slow, wandering herds
+            "grazer" => Self {
+                speed: 0.5,
+                behaviors: vec![
+                    (AVOID_RATE, Box::new(Separate { radius: AVOID_RADIUS })),
+                    (0.03, Box::new(Wander { jitter: 1.0 })),
+                    (0.02, Box::new(Flock { radius: 10.0 * HEAD_SIZE })),
+                    (0.02, Box::new(Flee { radius: 0.5 * FLEE_RADIUS })),
+                ],
+            },
+            // predators that see further and cut the worm off
+            "fast" => Self {
+                speed: 1.6,
+                behaviors: vec![
+                    (AVOID_RATE, Box::new(Separate { radius: AVOID_RADIUS })),
+                    (0.01, Box::new(Wander { jitter: 0.5 })),
+                    (0.05, Box::new(Pursue { radius: 2.0 * CHASE_RADIUS, ahead: 20.0 })),
+                ],
+            },
+            _ => return None,
+        };
+        Some(creature)
+    }
+
+    // how much the food at i turns this frame
+    pub fn turn(&self, world: &World, i: usize) -> f32 {
+        let angle = world.foods[i].angle;
+        self.behaviors.iter()
+            .filter_map(|(rate, b)| b.steer(world, i).map(|d| rate * ang_diff(d.angle(), angle)))
+            .sum()
+    }
+
+    pub fn reach(&self) -> f32 {
+        self.behaviors.iter().map(|(_, b)| b.radius()).fold(0.0, f32::max)
+    }
+}
@@ -0,0 +1,122 @@
+// What the physics and the foods are up to, drawn over the game: springs,
+// velocities, the steering force, the reach of the foods and how long the
+// frames take.
+
+use std::collections::VecDeque;
+
+use egui::{
+    Painter,
+    Vec2,
+    vec2,
+    pos2,
+    Color32,
+    Stroke,
+    Rect,
+    Align2,
+    FontId,
+};
+
+use crate::world::{World, AVOID_RADIUS};
+use crate::worm::Worm;
+
+const FRAMES:      usize = 180; // in the frame time graph
+const VEL_SCALE:   f32 = 5.0;   // pixels per pixel per frame
+const FORCE_SCALE: f32 = 100.0;
+const STRAIN_SCALE: f32 = 3.0;
+
+const STRETCHED:  Color32 = Color32::RED;
+const SQUEEZED:   Color32 = Color32::LIGHT_BLUE;
+const VELOCITY:   Color32 = Color32::GREEN;
+const FORCE:      Color32 = Color32::YELLOW;
+const FLEE:       Color32 = Color32::from_rgba_premultiplied(90, 90, 0, 90);
+const CHASE:      Color32 = Color32::from_rgba_premultiplied(100, 0, 0, 100);
+const AVOID:      Color32 = Color32::from_rgba_premultiplied(0, 80, 120, 120);
+
+
+#[derive(Default)]
+pub struct Debug {
+    pub forces:  Vec<Vec2>,       // last steering force of each player
+    frame_times: VecDeque<f32>,   // seconds
+}
+
+impl Debug {
+
+    pub fn frame(&mut self, dt: f32) {
+        self.frame_times.push_back(dt);
+        if self.frame_times.len() > FRAMES {
+            self.frame_times.pop_front();
+        }
+    }
+
+    // in world coordinates, on the painter of the world layer
+    pub fn paint_world(&self, painter: &Painter, world: &World) {
+        // each close pair once, found through the food grid
+        for i in 0..world.foods.len() {
+            for j in world.foods_near(i, AVOID_RADIUS).filter(|&j| j > i) {
+                let (a, b) = (world.foods[i].pos, world.foods[j].pos);
+                let d = world.delta(a, b);
+                painter.line_segment([a.to_pos2(), (a + d).to_pos2()], Stroke::new(1.0, AVOID));
+            }
+        }
+        for (i, fd) in world.foods.iter().enumerate() {
+            let reach = world.kind(i).reach();
+            if fd.tag.is_some() {
+                painter.circle_stroke(fd.tag_pos.to_pos2(), reach, Stroke::new(1.0, FLEE));
+            } else {
+                painter.circle_stroke(fd.pos.to_pos2(), reach, Stroke::new(1.0, CHASE));
+            }
+            painter.arrow(fd.pos.to_pos2(), 30.0 * Vec2::angled(fd.angle), Stroke::new(1.5, Color32::WHITE));
+        }
+
+        for (i, p) in world.players.iter().enumerate() {
+            paint_worm(painter, &p.worm);
+            if let Some(f) = self.forces.get(i) {
+                painter.arrow(p.worm.head.position.to_pos2(), FORCE_SCALE * *f, Stroke::new(2.5, FORCE));
+            }
+        }
+    }
+
+    // the frame time graph, on screen
+    pub fn paint_hud(&self, painter: &Painter, screen: Rect) {
+        let rect = Rect::from_min_size(pos2(20.0, screen.max.y - 100.0), vec2(2.0 * FRAMES as f32, 80.0));
+        painter.rect_filled(rect, 4.0, Color32::from_black_alpha(160));
+        let ms_to_y = |ms: f32| rect.max.y - (ms / 50.0).min(1.0) * rect.height();
+        let budget = ms_to_y(1000.0 / 60.0);
+        painter.line_segment([pos2(rect.min.x, budget), pos2(rect.max.x, budget)], Stroke::new(1.0, Color32::DARK_GREEN));
+        let points = self.frame_times.iter().enumerate()
+            .map(|(i, dt)| pos2(rect.min.x + 2.0 * i as f32, ms_to_y(1000.0 * dt)))
+            .collect();
+        painter.add(egui::Shape::line(points, Stroke::new(1.5, Color32::WHITE)));
+        if let Some(dt) = self.frame_times.back() {
+            let txt = format!("{:.1} ms", 1000.0 * dt);
+            painter.text(rect.min + vec2(6.0, 4.0), Align2::LEFT_TOP, txt, FontId::monospace(14.0), Color32::WHITE);
+        }
+    }
+}
+
+// each link coloured by how far it is stretched or squeezed, and the way
+// every part is moving
+fn paint_worm(painter: &Painter, worm: &Worm) {
+    let mut links = vec![(worm.head.position, worm.neck.position, worm.l0_lead)];
+    let mut lead = worm.neck.position;
+    for u in &worm.units {
+        links.push((lead, u.position, u.l0));
+        lead = u.position;
+    }
+    for (a, b, l0) in links {
+        let d = a - b;
+        let strain = d.length() - l0;
+        let color = if strain > 0.0 { STRETCHED } else { SQUEEZED };
+        painter.line_segment([a.to_pos2(), b.to_pos2()], Stroke::new(1.0, color));
+        if d.length() > 0.0 {
+            painter.arrow(b.to_pos2(), STRAIN_SCALE * strain * d.normalized(), Stroke::new(2.0, color));
+        }
+    }
+
+    let moving = [(worm.head.position, worm.head.velocity), (worm.neck.position, worm.neck.velocity)]
+        .into_iter()
+        .chain(worm.units.iter().map(|u| (u.position, u.velocity)));
+    for (pos, vel) in moving {
+        painter.arrow(pos.to_pos2(), VEL_SCALE * vel, Stroke::new(1.0, VELOCITY));
+    }
+}
@@ -0,0 +1,495 @@
+// The level editor. Obstacles and spawn zones are drawn with the mouse,
+// picked and dragged to move them; the handle at the end of a wall or the
+// corner of a zone resizes it. Everything is kept in level fractions, the
+// canvas only scales it for painting.
+
+use std::fs;
+use std::path::Path;
+use std::ops::RangeInclusive;
+
+use egui::{
+    Context, Ui, CentralPanel, SidePanel, RichText, ComboBox,
+    Vec2, vec2, Pos2, Rect, Color32, Stroke, Sense, Painter, Slider,
+};
+
+use crate::level::{self, Level, Obstacle};
+use crate::ai::SKILLS;
+use crate::creature::{LETTER_CREATURES, PREDATOR_CREATURES};
+use crate::power::POWERS;
+use crate::consts::{
+    LETTERS_SHOWN,
+    PREDATORS,
+    PALETTES,
+    PLAYER_GAP,
+    R_LEAD,
+};
+
+const WHITE:    Color32 = Color32::WHITE;
+const SPAWN:    Color32 = Color32::from_rgba_premultiplied(30, 90, 30, 60);
+const SELECTED: Color32 = Color32::YELLOW;
+const HANDLE:   f32     = 8.0; // pixels around an end or corner that grab it
+
+
+#[derive(PartialEq, Clone, Copy)]
+enum Tool {
+    Select,
+    Rock,
+    Wall,
+    Water,
+    Spawn,
+    Start,
+}
+
+const TOOLS: [Tool; 6] = [Tool::Select, Tool::Rock, Tool::Wall, Tool::Water, Tool::Spawn, Tool::Start];
+
+impl Tool {
+    fn label(&self) -> &'static str {
+        match self {
+            Tool::Select => "Select / move",
+            Tool::Rock   => "Rock",
+            Tool::Wall   => "Wall",
+            Tool::Water  => "Water",
+            Tool::Spawn  => "Food zone",
+            Tool::Start  => "Worm start",
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy)]
+enum Item {
+    Obstacle(usize),
+    Spawn(usize),
+}
+
+// something new drawn with a tool
+#[derive(Clone, Copy)]
+enum Piece {
+    Obstacle(Obstacle),
+    Zone(Rect),
+}
+
+// what the mouse holds: the whole item, the start of a wall or the far end
+#[derive(PartialEq, Clone, Copy)]
+enum Grab {
+    Whole,
+    From,
+    To,
+}
+
+
+pub struct Editor {
+    pub level: Level,
+    file:      String, // saved as levels/<file>.level
+    tool:      Tool,
+    selected:  Option<Item>,
+    grab:      Grab,
+    drag_from: Option<Vec2>,
+    message:   String,
+}
+
+impl Editor {
+    pub fn new(level: Level) -> Self {
+        let mut level = level;
+        if level.obstacles.is_empty() && level.spawns.is_empty() {
+            level.name = "New level".to_owned();
+        }
+        Self {
+            file:      level.name.to_lowercase().replace(' ', "-"),
+            level,
+            tool:      Tool::Rock,
+            selected:  None,
+            grab:      Grab::Whole,
+            drag_from: None,
+            message:   String::new(),
+        }
+    }
+
+    // returns true when done
+    pub fn show(&mut self, ctx: &Context, vocabs: &[String]) -> bool {
+        let mut done = false;
+        SidePanel::left("editor_panel").show(ctx, |ui| {
+            done = self.side_widgets(ui, vocabs);
+        });
+        CentralPanel::default().show(ctx, |ui| {
+            self.canvas(ui);
+        });
+        done
+    }
+
+    fn side_widgets(&mut self, ui: &mut Ui, vocabs: &[String]) -> bool {
+        ui.add_space(10.0);
+        ui.label(RichText::new("Level editor").size(24.0).color(WHITE));
+        ui.add_space(10.0);
+        ui.horizontal(|ui| {
+            ui.label("Name:");
+            ui.text_edit_singleline(&mut self.level.name);
+        });
+        ui.horizontal(|ui| {
+            ui.label("File:");
+            ui.text_edit_singleline(&mut self.file);
+            ui.label(format!(".{}", level::EXTENSION));
+        });
+        ui.add_space(20.0);
+
+        ui.label(RichText::new("Tool:").size(18.0).color(Color32::RED));
+        for t in TOOLS {
+            ui.radio_value(&mut self.tool, t, t.label());
+        }
+        ui.add_space(10.0);
+        self.selected_widgets(ui);
+        ui.add_space(20.0);
+
+        ui.label(RichText::new("Vocabulary:").size(18.0).color(Color32::RED));
+        let current = self.level.vocab.clone().unwrap_or("(any)".to_owned());
+        ComboBox::from_id_salt("level_vocab").selected_text(current).show_ui(ui, |ui| {
+            ui.selectable_value(&mut self.level.vocab, None, "(any)");
+            for v in vocabs {
+                ui.selectable_value(&mut self.level.vocab, Some(v.to_owned()), v);
+            }
+        });
+        ui.add_space(10.0);
+        ui.label(RichText::new("Difficulty:").size(18.0).color(Color32::RED));
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.level.difficulty, None, "(any)");
+            for k in SKILLS {
+                ui.radio_value(&mut self.level.difficulty, Some(k), k.label());
+            }
+        });
+        ui.add_space(10.0);
+        ui.label(RichText::new("Foods:").size(18.0).color(Color32::RED));
+        count_widget(ui, "letters shown", &mut self.level.letters_shown, LETTERS_SHOWN, 1..=level::MAX_SHOWN);
+        let usual = self.level.difficulty.map(|d| d.predators()).unwrap_or(PREDATORS);
+        count_widget(ui, "predators", &mut self.level.predator_count, usual, 0..=level::MAX_PREDATORS);
+        ui.add_space(10.0);
+        ui.label(RichText::new("Creatures:").size(18.0).color(Color32::RED));
+        creature_box(ui, "Letters", &mut self.level.letters, &LETTER_CREATURES);
+        creature_box(ui, "Predators", &mut self.level.predators, &PREDATOR_CREATURES);
+        ui.add_space(10.0);
+        ui.label(RichText::new("Power-ups:").size(18.0).color(Color32::RED));
+        let mut kinds = self.level.power_kinds();
+        let mut changed = false;
+        for p in POWERS {
+            let mut on = kinds.contains(&p);
+            if ui.checkbox(&mut on, p.label()).changed() {
+                kinds.retain(|k| *k != p);
+                if on {
+                    kinds.push(p);
+                }
+                changed = true;
+            }
+        }
+        if changed {
+            // in the usual order, and left out of the file when it is all of them
+            kinds = POWERS.into_iter().filter(|p| kinds.contains(p)).collect();
+            self.level.powers = (kinds.len() < POWERS.len()).then_some(kinds);
+        }
+        ui.add_space(20.0);
+
+        let mut done = false;
+        ui.horizontal(|ui| {
+            if ui.button(RichText::new("Save").size(18.0)).clicked() {
+                self.save();
+            }
+            if ui.button(RichText::new("Clear").size(18.0)).clicked() {
+                self.level.obstacles.clear();
+                self.level.spawns.clear();
+                self.level.start = None;
+                self.selected = None;
+            }
+            if ui.button(RichText::new("Done").size(18.0)).clicked() {
+                done = true;
+            }
+        });
+        if !self.message.is_empty() {
+            ui.label(RichText::new(&self.message).color(Color32::YELLOW));
+        }
+        done
+    }
+
+    fn selected_widgets(&mut self, ui: &mut Ui) {
+        let Some(item) = self.selected else {
+            ui.label("Click an obstacle or zone to select it.");
+            return;
+        };
+        match item {
+            Item::Obstacle(i) => match &mut self.level.obstacles[i] {
+                Obstacle::Rock { radius, .. } => {
+                    ui.add(Slider::new(radius, 5.0..=200.0).text("radius"));
+                }
+                Obstacle::Wall { width, .. } => {
+                    ui.add(Slider::new(width, 4.0..=60.0).text("width"));
+                }
+                Obstacle::Water { .. } => {
+                    ui.label("Drag the corner to resize.");
+                }
+            },
+            Item::Spawn(_) => {
+                ui.label("Drag the corner to resize.");
+            }
+        }
+        if ui.button("Delete").clicked() {
+            match item {
+                Item::Obstacle(i) => { self.level.obstacles.remove(i); }
+                Item::Spawn(i)    => { self.level.spawns.remove(i); }
+            }
+            self.selected = None;
+        }
+    }
+
+    fn save(&mut self) {
+        let name = self.file.trim();
+        if name.is_empty() || name.contains(['/', '\\']) {
+            self.message = "Give the file a plain name.".to_owned();
+            return;
+        }
+        let (room, needed) = (self.level.food_room(level::NORMAL_ARENA), self.level.most_foods());
+        if room < needed {
+            self.message = format!("The food zones hold about {} foods, the counts need room for {}.", room, needed);
+            return;
+        }
+        let path = Path::new(level::DIR).join(format!("{}.{}", name, level::EXTENSION));
+        let result = fs::create_dir_all(level::DIR).and_then(|_| self.level.save(&path));
+        self.message = match result {
+            Ok(_) => format!("Saved {}", path.display()),
+            Err(e) => format!("Cannot save {}: {}", path.display(), e),
+        };
+    }
+
+    fn canvas(&mut self, ui: &mut Ui) {
+        let rect = ui.available_rect_before_wrap();
+        let response = ui.allocate_rect(rect, Sense::click_and_drag());
+        let size = rect.size();
+        let frac = |p: Pos2| (p - rect.min) / size;
+        let pointer = ui.input(|i| i.pointer.latest_pos()).map(frac);
+
+        if response.clicked() {
+            if let Some(p) = pointer {
+                self.click(p, size);
+            }
+        }
+        if response.drag_started() {
+            if let Some(p) = pointer {
+                if self.tool == Tool::Select {
+                    self.grab(p, size);
+                } else {
+                    self.drag_from = Some(p);
+                }
+            }
+        }
+        if response.dragged() && self.tool == Tool::Select {
+            self.move_selected(response.drag_delta() / size);
+        }
+        let preview = match (self.drag_from, pointer) {
+            (Some(a), Some(b)) => self.shape(a, b, size),
+            _ => None,
+        };
+        if response.drag_stopped() {
+            if let Some(piece) = preview {
+                self.add(piece);
+            }
+            self.drag_from = None;
+            self.tidy();
+        }
+
+        let painter = ui.painter_at(rect);
+        self.paint(&painter, rect);
+        match preview {
+            Some(Piece::Obstacle(o)) => o.scaled(size).translated(rect.min.to_vec2()).paint(&painter),
+            Some(Piece::Zone(z)) => paint_zone(&painter, to_screen(z, rect), Color32::GREEN),
+            None => {}
+        }
+    }
+
+    fn click(&mut self, p: Vec2, size: Vec2) {
+        let px = vec2(1.0 / size.x, 1.0 / size.y); // one pixel in fractions
+        let new = match self.tool {
+            Tool::Select => {
+                self.selected = self.pick(p, size);
+                return;
+            }
+            Tool::Start => {
+                self.level.start = Some(p);
+                return;
+            }
+            Tool::Rock  => self.shape(p, p + 40.0 * vec2(px.x, 0.0), size),
+            Tool::Wall  => self.shape(p - 60.0 * vec2(px.x, 0.0), p + 60.0 * vec2(px.x, 0.0), size),
+            Tool::Water | Tool::Spawn => self.shape(p - 60.0 * px, p + 60.0 * px, size),
+        };
+        if let Some(piece) = new {
+            self.add(piece);
+        }
+    }
+
+    // the obstacle or zone a drag from a to b draws
+    fn shape(&self, a: Vec2, b: Vec2, size: Vec2) -> Option<Piece> {
+        let rect = Rect::from_two_pos(a.to_pos2(), b.to_pos2());
+        match self.tool {
+            Tool::Rock  => Some(Piece::Obstacle(Obstacle::Rock { center: a, radius: ((b - a) * size).length().max(5.0) })),
+            Tool::Wall  => Some(Piece::Obstacle(Obstacle::Wall { from: a, to: b, width: 16.0 })),
+            Tool::Water => Some(Piece::Obstacle(Obstacle::Water { min: rect.min.to_vec2(), max: rect.max.to_vec2() })),
+            Tool::Spawn => Some(Piece::Zone(rect)),
+            _ => None,
+        }
+    }
+
+    fn add(&mut self, piece: Piece) {
+        match piece {
+            Piece::Obstacle(o) => {
+                self.level.obstacles.push(o);
+                self.selected = Some(Item::Obstacle(self.level.obstacles.len() - 1));
+            }
+            Piece::Zone(z) => {
+                self.level.spawns.push(z);
+                self.selected = Some(Item::Spawn(self.level.spawns.len() - 1));
+            }
+        }
+    }
+
+    // the topmost obstacle or zone under the pointer
+    fn pick(&self, p: Vec2, size: Vec2) -> Option<Item> {
+        let px = p * size;
+        let hit = self.level.obstacles.iter().rposition(|o| o.scaled(size).covers(px, 2.0));
+        if let Some(i) = hit {
+            return Some(Item::Obstacle(i));
+        }
+        self.level.spawns.iter().rposition(|z| z.contains(p.to_pos2())).map(Item::Spawn)
+    }
+
+    // a handle of the selected item, or whatever is under the pointer
+    fn grab(&mut self, p: Vec2, size: Vec2) {
+        let near = |q: Vec2| ((q - p) * size).length() < HANDLE;
+        self.grab = match self.selected {
+            Some(Item::Obstacle(i)) => match self.level.obstacles[i] {
+                Obstacle::Wall { from, .. } if near(from) => Grab::From,
+                Obstacle::Wall { to, .. } if near(to) => Grab::To,
+                Obstacle::Water { max, .. } if near(max) => Grab::To,
+                _ => Grab::Whole,
+            },
+            Some(Item::Spawn(i)) if near(self.level.spawns[i].max.to_vec2()) => Grab::To,
+            _ => Grab::Whole,
+        };
+        if self.grab == Grab::Whole {
+            self.selected = self.pick(p, size);
+        }
+    }
+
+    fn move_selected(&mut self, d: Vec2) {
+        let grab = self.grab;
+        match self.selected {
+            Some(Item::Obstacle(i)) => {
+                let o = &mut self.level.obstacles[i];
+                match (grab, o) {
+                    (Grab::Whole, o) => *o = o.translated(d),
+                    (Grab::From, Obstacle::Wall { from, .. }) => *from += d,
+                    (Grab::To, Obstacle::Wall { to, .. }) => *to += d,
+                    (Grab::To, Obstacle::Water { max, .. }) => *max += d,
+                    _ => {}
+                }
+            }
+            Some(Item::Spawn(i)) => {
+                let z = &mut self.level.spawns[i];
+                match grab {
+                    Grab::Whole => *z = z.translate(d),
+                    _ => z.max += d,
+                }
+            }
+            None => {}
+        }
+    }
+
+    // corners dragged past each other swap back
+    fn tidy(&mut self) {
+        for o in &mut self.level.obstacles {
+            if let Obstacle::Water { min, max } = o {
+                let r = Rect::from_two_pos(min.to_pos2(), max.to_pos2());
+                (*min, *max) = (r.min.to_vec2(), r.max.to_vec2());
+            }
+        }
+        for z in &mut self.level.spawns {
+            *z = Rect::from_two_pos(z.min, z.max);
+        }
+    }
+
+    fn paint(&self, painter: &Painter, rect: Rect) {
+        let size = rect.size();
+        let offset = rect.min.to_vec2();
+        painter.rect_stroke(rect, 0.0, Stroke::new(1.0, Color32::GRAY));
+
+        for (i, z) in self.level.spawns.iter().enumerate() {
+            let color = if self.selected == Some(Item::Spawn(i)) { SELECTED } else { Color32::GREEN };
+            paint_zone(painter, to_screen(*z, rect), color);
+        }
+        for (i, o) in self.level.obstacles.iter().enumerate() {
+            let o = o.scaled(size).translated(offset);
+            o.paint(painter);
+            if self.selected == Some(Item::Obstacle(i)) {
+                outline(painter, &o);
+            }
+        }
+
+        // the worms start one below the other
+        let start = self.level.start(size) + offset;
+        for (i, palette) in PALETTES.iter().enumerate() {
+            let pos = start + vec2(0.0, i as f32 * PLAYER_GAP);
+            painter.circle_stroke(pos.to_pos2(), R_LEAD, Stroke::new(2.0, palette[0]));
+        }
+        painter.circle_filled(start.to_pos2(), R_LEAD, PALETTES[0][0]);
+    }
+}
+
+
+fn to_screen(z: Rect, rect: Rect) -> Rect {
+    let size = rect.size();
+    Rect::from_min_max(rect.min + z.min.to_vec2() * size, rect.min + z.max.to_vec2() * size)
+}
+
+fn paint_zone(painter: &Painter, zone: Rect, color: Color32) {
+    painter.rect_filled(zone, 0.0, SPAWN);
+    painter.rect_stroke(zone, 0.0, Stroke::new(1.5, color));
+    painter.circle_filled(zone.max, 0.5 * HANDLE, color);
+}
+
+fn outline(painter: &Painter, o: &Obstacle) {
+    let stroke = Stroke::new(2.0, SELECTED);
+    match *o {
+        Obstacle::Rock { center, radius } => {
+            painter.circle_stroke(center.to_pos2(), radius + 2.0, stroke);
+        }
+        Obstacle::Wall { from, to, .. } => {
+            painter.circle_filled(from.to_pos2(), 0.5 * HANDLE, SELECTED);
+            painter.circle_filled(to.to_pos2(), 0.5 * HANDLE, SELECTED);
+        }
+        Obstacle::Water { min, max } => {
+            painter.rect_stroke(Rect::from_min_max(min.to_pos2(), max.to_pos2()), 8.0, stroke);
+            painter.circle_filled(max.to_pos2(), 0.5 * HANDLE, SELECTED);
+        }
+    }
+}
+
+
+// a number the level may set, or leave to the usual
+fn count_widget(ui: &mut Ui, label: &str, value: &mut Option<usize>, usual: usize, range: RangeInclusive<usize>) {
+    ui.horizontal(|ui| {
+        let mut set = value.is_some();
+        if ui.checkbox(&mut set, label).changed() {
+            *value = set.then_some(usual);
+        }
+        match value {
+            Some(n) => { ui.add(Slider::new(n, range)); }
+            None => { ui.label(format!("{} (usual)", usual)); }
+        }
+    });
+}
+
+fn creature_box(ui: &mut Ui, label: &str, value: &mut Option<String>, names: &[&str]) {
+    ui.horizontal(|ui| {
+        ui.label(label);
+        let current = value.clone().unwrap_or("(usual)".to_owned());
+        ComboBox::from_id_salt(label).selected_text(current).show_ui(ui, |ui| {
+            ui.selectable_value(value, None, "(usual)");
+            for n in names {
+                ui.selectable_value(value, Some(n.to_string()), *n);
+            }
+        });
+    });
+}
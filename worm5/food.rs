@@ -12,6 +12,7 @@ use egui::{
     Stroke,
 };
 
+use crate::power::Power;
 use crate::consts::{
     HALF_SIZE,
     FOOD_LEN,
@@ -23,10 +24,11 @@ use crate::consts::{
     EYE1,
     EYE2,
     OMEGA,
+    LETTER_COLORS,
+    wrap_pos,
 };
 
 
-const DARKRED:   Color32 = Color32::DARK_RED;
 const DARKGREEN: Color32 = Color32::DARK_GREEN;
 const YELLOW:    Color32 = Color32::YELLOW;
 const WHITE:     Color32 = Color32::WHITE;
@@ -38,10 +40,14 @@ pub struct Food {
     pub id:      usize,
     pub pos:     Vec2,
     pub tag:     Option<char>,
+    pub owner:   usize, // the word the letter belongs to
+    pub seq:     usize, // where the letter is in the word
     pub tag_pos: Vec2,
     pub angle:   f32,
     pub speed:   f32,
     pub t0:      Instant,
+    pub power:   Option<Power>, // a power-up instead of a letter or predator
+    pub life:    u32,           // frames a power-up has left
 }
 
 
@@ -51,10 +57,14 @@ impl Default for Food {
             id:      0,
             pos:     vec2(0.0, 0.0),
             tag:     None,
+            owner:   0,
+            seq:     0,
             angle:   0.0,
             speed:   FOOD_SPEED,
             tag_pos: vec2(0.0, 0.0),
             t0:      Instant::now(),
+            power:   None,
+            life:    0,
         }
     }
 }
@@ -62,7 +72,12 @@ impl Default for Food {
 
 impl Food {
 
-    pub fn move_me(&mut self, ui_size: Vec2) { // new
+    pub fn move_me(&mut self, ui_size: Vec2, wrap: bool) { // new
+        if wrap {
+            self.pos += self.speed * Vec2::angled(self.angle);
+            self.pos = wrap_pos(self.pos, ui_size);
+            return;
+        }
         let rx = if self.pos.x <= 0.0 {
             self.angle = PI - self.angle;
             2.0
@@ -92,10 +107,16 @@ impl Food {
     pub fn paint(&mut self, painter: &Painter) {
         self.wag_tail(); // new
 
-        if let Some(letter) = self.tag {
-            painter.circle_filled(self.tag_pos.to_pos2(), R_TAIL, DARKRED);
+        if let Some(power) = self.power {
+            let fade = if self.life < 120 { self.life as f32 / 120.0 } else { 1.0 };
+            painter.circle_filled(self.pos.to_pos2(), HALF_SIZE, power.color().gamma_multiply(fade));
+            painter.circle_stroke(self.pos.to_pos2(), HALF_SIZE, Stroke::new(2.0, WHITE.gamma_multiply(fade)));
+            painter.text(self.pos.to_pos2(), CENTER, power.symbol(), FONT_ID, WHITE.gamma_multiply(fade));
+        } else if let Some(letter) = self.tag {
+            let color = LETTER_COLORS[self.owner % LETTER_COLORS.len()];
+            painter.circle_filled(self.tag_pos.to_pos2(), R_TAIL, color);
             painter.text(self.tag_pos.to_pos2(), CENTER, letter, FONT_ID, WHITE );
-            painter.circle_filled(self.pos.to_pos2(), HALF_SIZE, DARKRED);
+            painter.circle_filled(self.pos.to_pos2(), HALF_SIZE, color);
         } else {
             painter.circle_filled(self.tag_pos.to_pos2(), R_TAIL, DARKGREEN);
             let body: Shape = build_shape(self.pos, self.angle, BODY, DARKGREEN); // new
@@ -118,11 +139,6 @@ impl Food {
         self
     }
 
-    pub fn set_tag(mut self, tag: Option<char>) -> Self {
-        self.tag = tag;
-        self
-    }
-
     pub fn wag_tail(&mut self) {
         self.tag_pos.x = self.pos.x - FOOD_LEN * self.angle.cos();
         self.tag_pos.y = self.pos.y - FOOD_LEN * self.angle.sin();
@@ -1,87 +1,141 @@
 use std::sync::Arc;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::fs;
 
-use std::thread;
 use rand::Rng;
-use std::f32::consts::PI;
 
 use egui::{
     Context, Ui, CentralPanel, Key, ViewportCommand,
     SidePanel, RichText, Vec2, vec2, Color32, Button,
     FontFamily, FontData, FontDefinitions, Grid,
-    TextStyle, Response,
-};
-
-use ears::{
-    Sound,
-    AudioController
+    TextStyle, Response, Align2, FontId, pos2,
+    Event, Slider, Stroke, LayerId, Order, Id, Rect, Pos2,
+    Modal, ScrollArea,
+    emath::TSTransform,
 };
 
 const HEADING:  TextStyle = TextStyle::Heading;
 const WHITE:    Color32   = Color32::WHITE;
-const MIN_DIST: f32       = 2.0 * R_LEAD;
-const FIVE:     usize     = 5;
-const TOUCH:    f32       = R_UNIT + 0.5 * HEAD_SIZE;
+const PACK_DIR: &str      = "packs";
 
 use crate::consts::{
-    GameState,
-    AVOID_RATE,
-    HEAD_SIZE,
-    FOOD_LEN,
+    Screen,
+    PlayMode,
+    LetterAudio,
     DIAMETER,
-    MAX_FORWARD,
-    MAX_TURN,
     R_LEAD,
-    R_UNIT,
-    PURPLE1,
+    PALETTES,
+    LETTER_COLORS,
+    ArenaSize,
+    ARENA_SIZES,
+    PLAY_MODES,
     ZOO_ANIMALS,
-    SEA_ANIMALS,
-    BIRDS,
-    INSECTS,
-    FARM_ANIMALS,
-    BODY_PARTS,
-    FRUITS,
-    VEGETABLES,
-    FOOD_AND_DRINKS,
-    SPORT_AND_GAMES,
-    ang_diff,
+    VOCABS,
+    MAX_FORWARD,
+    MAX_TURN,
 };
 
 
 use crate::food::Food;
-use crate::worm::Worm;
+use crate::world::{self, World};
+use crate::audio::Audio;
+use crate::pack::{self, Pack};
+use crate::controls::{Controls, Action, Scheme, SteerMode, ACTIONS, STEER_MODES};
+use crate::ai::{Ai, Skill, SKILLS};
+use crate::spelling::Spelling;
+use crate::net::{self, Connection, ClientMsg};
+use crate::level::{self, Level};
+use crate::editor::Editor;
+use crate::camera::Camera;
+use crate::body::{self, Param};
+use crate::tuning::{self, Tuning};
+use crate::debug::Debug;
+use crate::power::Power;
+
+
+// a screen change asked for during a frame, made at the start of the next
+enum Change {
+    Push(Screen),
+    Pop,
+    Switch(Screen), // replaces the top screen
+    Menu,           // back to the bottom of the stack
+}
 
 
 pub struct Game {
-    paused:      bool,
-    worm:        Worm,
+    world:       World,
     vocabulary:  Vec<String>,
-    word:        String,
-    foods:       Vec<Food>,
-    game_state:  GameState,
-    char_stack:  Vec<char>,
-    n_chars:     usize,
-    forward_f:   f32,
-    left_f:      f32,
-    right_f:     f32,
+    screens:     Vec<Screen>, // the top one is shown
+    changes:     Vec<Change>,
     canvas_size: Vec2,
+    letter_audio: LetterAudio,
+    audio:       Audio,
+    packs:       Vec<(String, PathBuf)>,
+    emojis:      HashMap<String, String>,
+    controls:    Controls,
+    rebinding:   Option<Action>,
+    play_mode:   PlayMode,
+    pads:        Vec<Controls>, // the movement keys of each player
+    message:     String,
+    skill:       Skill,
+    self_penalty: bool,
+    wrap:        bool,
+    constraints: bool,
+    levels:      Vec<Level>, // the first one is the open field
+    level:       usize,
+    editor:      Option<Editor>,
+    arena:       ArenaSize,
+    camera:      Camera,
+    me:          usize, // the player followed by the camera on a server
+    server_addr: String,
+    net:         Option<Connection>, // playing on a caterpillar-server
+    net_status:  String,
+    tuning:      Tuning,
+    tuning_open: bool, // the tuning panel
+    presets:     Vec<(String, Vec<(String, f32)>)>,
+    preset_name: String,
+    debug:       Debug,
+    debug_open:  bool, // the debug overlay
 }
 
 
 impl Default for Game {
     fn default() -> Self {
         Self {
-            paused:      false,
-            worm:        Worm::default(),
+            world:       World::default(),
             vocabulary:  ZOO_ANIMALS.iter().map(|s| s.to_string()).collect(),
-            word:        "llama".to_string(),
-            foods:       Vec::new(),
-            game_state:  GameState::StartUI,
-            char_stack:  Vec::new(),
-            n_chars:     0,
-            forward_f:   0.0,
-            left_f:      0.0,
-            right_f:     0.0,
+            screens:     vec![Screen::Menu],
+            changes:     Vec::new(),
             canvas_size: vec2(0.0, 0.0),
+            letter_audio: LetterAudio::Off,
+            audio:       Audio::default(),
+            packs:       find_packs(),
+            emojis:      HashMap::new(),
+            controls:    Controls::load(),
+            rebinding:   None,
+            play_mode:   PlayMode::Solo,
+            pads:        Vec::new(),
+            message:     String::new(),
+            skill:       Skill::Normal,
+            self_penalty: false,
+            wrap:        false,
+            constraints: false,
+            levels:      find_levels(),
+            level:       0,
+            editor:      None,
+            arena:       ArenaSize::Window,
+            camera:      Camera::default(),
+            me:          0,
+            server_addr: net::DEFAULT_ADDR.to_owned(),
+            net:         None,
+            net_status:  String::new(),
+            tuning:      Tuning::default(),
+            tuning_open: false,
+            presets:     tuning::load_presets(),
+            preset_name: String::new(),
+            debug:       Debug::default(),
+            debug_open:  false,
         }
     }
 }
@@ -93,177 +147,476 @@ impl Game {
         Default::default()
     }
 
-    fn reset(&mut self) {
-        *self = Self::default();
+    fn screen(&self) -> Screen {
+        *self.screens.last().unwrap()
     }
-}
 
-impl Game {
+    fn push(&mut self, screen: Screen) {
+        self.changes.push(Change::Push(screen));
+    }
 
-    fn choose_word(&mut self) {
-        let mut rng = rand::thread_rng();
-        let rand_i = rng.gen_range(0..self.vocabulary.len());
-        self.word = self.vocabulary[rand_i].clone();
-        self.char_stack = self.word.chars().collect();
-        self.char_stack.reverse();
-        let s = format!("sounds/{}.wav", self.word);
-        thread::spawn(move|| {
-            let mut snd = Sound::new(&s).unwrap();
-            snd.play();
-            while snd.is_playing() {}
-        });
+    fn pop(&mut self) {
+        self.changes.push(Change::Pop);
+    }
 
+    fn switch(&mut self, screen: Screen) {
+        self.changes.push(Change::Switch(screen));
     }
 
-    fn create_foods(&mut self) {
-        self.foods.clear();
-        let mut rng = rand::thread_rng();
-        let mut id: usize = 0;
-        while self.foods.len() < 5 {
-            let x = rng.gen_range(DIAMETER..self.canvas_size.x-DIAMETER);
-            let y = rng.gen_range(DIAMETER..self.canvas_size.y-DIAMETER);
-            let new_pos = Vec2 { x, y };
-            let mut push = true;
-            for fd in &self.foods {
-                if (fd.pos - new_pos).length() < 2.0 * DIAMETER {
-                    push = false;
-                    break
+    fn back_to_menu(&mut self) {
+        self.changes.push(Change::Menu);
+    }
+
+    fn apply_changes(&mut self) {
+        for change in std::mem::take(&mut self.changes) {
+            match change {
+                Change::Push(screen) => {
+                    self.screens.push(screen);
+                    self.enter(screen);
+                }
+                Change::Pop if self.screens.len() > 1 => {
+                    let screen = self.screens.pop().unwrap();
+                    self.exit(screen);
+                }
+                Change::Pop => {}
+                Change::Switch(screen) => {
+                    if self.screens.len() > 1 {
+                        let old = self.screens.pop().unwrap();
+                        self.exit(old);
+                    }
+                    self.screens.push(screen);
+                    self.enter(screen);
+                }
+                Change::Menu => {
+                    while self.screens.len() > 1 {
+                        let screen = self.screens.pop().unwrap();
+                        self.exit(screen);
+                    }
                 }
-            }
-            if (self.worm.head.position - new_pos).length() < 4.0 * DIAMETER {
-                push = false;
-            }
-            if push {
-                let tag: Option<char> = if id < 3 { self.char_stack.pop() } else { None };
-                let food = Food::default().set_id(id).set_pos(new_pos).set_tag(tag);
-                self.foods.push(food);
-                id += 1;
             }
         }
-        self.n_chars = 3;
     }
 
-
-    fn avoid(&mut self, i: usize, j: usize) {
-        let seg = self.foods[i].pos - self.foods[j].pos;
-        if seg.length() < 5.0 * HEAD_SIZE {
-            let ang1 = seg.angle();
-            let ang0 = { if ang1 > 0.0 {ang1 - PI} else {ang1 + PI} };
-            self.foods[i].angle += ang_diff(ang1, self.foods[i].angle) * AVOID_RATE;
-            self.foods[j].angle += ang_diff(ang0, self.foods[j].angle) * AVOID_RATE;
+    fn enter(&mut self, screen: Screen) {
+        match screen {
+            Screen::Play if self.net.is_none() => self.start(),
+            Screen::Editor => self.editor = Some(Editor::new(self.levels[self.level].clone())),
+            _ => {}
         }
     }
 
-    fn find_food(&mut self) -> Option<usize> {
-        for i in 0..self.foods.len() {
-            if self.foods[i].tag == None {
-                continue;
+    fn exit(&mut self, screen: Screen) {
+        match screen {
+            Screen::Play => self.net = None,
+            Screen::Controls => {
+                self.controls.save();
+                self.rebinding = None;
             }
-            let vector = self.foods[i].tag_pos - self.worm.head.position;
-            let distance = vector.length();
-            if distance > 6.0 * HEAD_SIZE { // too far
-                continue;
+            Screen::Editor => {
+                if let Some(editor) = self.editor.take() {
+                    self.levels = find_levels();
+                    self.level = self.levels.iter().position(|l| l.name == editor.level.name).unwrap_or(0);
+                }
             }
+            _ => {}
+        }
+    }
+}
 
-            if  distance > DIAMETER { // flee
-                let ang = vector.angle();
-                self.foods[i].angle += ang_diff(ang, self.foods[i].angle)/30.0; // 10.0, 5.0, 40.0 too slow
-                continue;
+impl Game {
+
+    fn start(&mut self) {
+        let mut rng = rand::thread_rng();
+        let mut words: Vec<String> = Vec::new();
+        while words.len() < self.play_mode.n_words() {
+            let rand_i = rng.gen_range(0..self.vocabulary.len());
+            let word = self.vocabulary[rand_i].clone();
+            if !words.contains(&word) || self.vocabulary.len() == 1 {
+                words.push(word);
             }
+        }
 
-            return Some(i);
+        // two players share the keyboard: arrows and WASD
+        self.pads = match self.play_mode {
+            PlayMode::Solo | PlayMode::VsComputer => vec![Controls::from_scheme(self.controls.scheme)],
+            _ => vec![Controls::from_scheme(Scheme::Arrows), Controls::from_scheme(Scheme::Wasd)],
+        };
+        for pad in &mut self.pads {
+            pad.ramp = self.controls.ramp;
         }
-        return None;
+        if self.pads.len() == 1 && self.controls.scheme == Scheme::Custom {
+            self.pads[0] = self.controls.clone();
+        }
+
+        let size = self.canvas_size * self.arena.scale();
+        self.world.size = size;
+        self.world.self_penalty = self.self_penalty;
+        self.world.wrap = self.wrap;
+        self.world.constraints = self.constraints;
+        let level = &self.levels[self.level];
+        self.world.obstacles = level.obstacles(size);
+        self.world.spawns    = level.spawns(size);
+        self.world.start     = level.start(size);
+        self.world.letter_kind   = level.letter_kind();
+        self.world.predator_kind = level.predator_kind();
+        self.world.power_kinds   = level.power_kinds();
+        self.world.predators = level.n_predators();
+        self.world.letters_shown = (level.n_shown() as f32 * self.arena.scale()) as usize;
+        self.world.start(self.play_mode, words, Some(Ai::new(self.skill)));
+        self.message.clear();
+        self.camera.jump(self.focus(), self.view(), size);
+        self.play_words();
     }
 
-    /*fn catch_worm1(&mut self, pos: Vec2) {
-        for id in self.n_chars..self.foods.len() {
-            if (self.foods[id].pos - pos).length() > TOUCH {
-                continue;
-            }
-            let unit = self.worm.units.pop().unwrap();
-            self.foods[id].tag = Some(unit.letter);
-            let _ = &self.foods[0..id+1].rotate_right(1);
-            if self.n_chars == 3 {
-                let lett = self.foods[3].tag;
-                self.foods[3].tag = None;
-                self.char_stack.push(lett.unwrap());
-            } else {
-                self.n_chars += 1;
-            }
-            if self.worm.units.len() < 2 {
-                self.game_state = GameState::GameOverUI;
-                self.play_audio("screech".to_owned());
-                self.worm.head.velocity = vec2(0.0, 0.0);
-                break;
+    // the worms played on this screen: the people's, or ours on a server
+    fn local_players(&self) -> Vec<usize> {
+        if self.net.is_some() {
+            return (self.me < self.world.players.len()).then_some(self.me).into_iter().collect();
+        }
+        (0..self.world.players.len()).filter(|&i| self.world.players[i].ai.is_none()).collect()
+    }
+
+    // the arena of a server is fitted to the window, whatever size it has
+    // there; a local one is painted at its own size
+    fn zoom(&self) -> f32 {
+        let size = self.world.size;
+        if self.net.is_none() || size.x <= 0.0 || size.y <= 0.0 {
+            return 1.0;
+        }
+        (self.canvas_size.x / size.x).min(self.canvas_size.y / size.y)
+    }
+
+    // the part of the world the window has room for
+    fn view(&self) -> Vec2 {
+        self.canvas_size / self.zoom()
+    }
+
+    // what the camera follows: the local worms
+    fn focus(&self) -> Vec2 {
+        let heads: Vec<Vec2> = self.local_players().into_iter()
+            .map(|i| self.world.players[i].worm.head.position)
+            .collect();
+        if heads.is_empty() {
+            return self.camera.center;
+        }
+        heads.iter().fold(Vec2::ZERO, |a, &h| a + h) / heads.len() as f32
+    }
+
+    fn play_words(&mut self) {
+        let words = self.world.spellings.iter().map(|s| s.word.to_owned()).collect();
+        self.play_audios(words);
+    }
+
+    fn handle_events(&mut self) {
+        let events = std::mem::take(&mut self.world.events);
+        for event in events {
+            match event {
+                world::Event::Caught { letter } => {
+                    let mut sounds = vec!["bite".to_owned()];
+                    sounds.extend(self.letter_audio.file(letter));
+                    self.play_audios(sounds);
+                }
+                world::Event::Stolen | world::Event::Dropped => {
+                    self.play_audio("oops".to_owned());
+                }
+                world::Event::PowerUp { player, power } => {
+                    let mut sounds = vec!["bite".to_owned()];
+                    if power == Power::Hint {
+                        let owner = self.world.players[player].spelling;
+                        let next = self.world.spellings[owner].next_letter();
+                        sounds.extend(next.and_then(|c| self.letter_audio.file(c)));
+                    }
+                    self.play_audios(sounds);
+                }
+                world::Event::WordDone { player } => {
+                    let mut sounds = vec!["cheering".to_owned()];
+                    if self.letter_audio != LetterAudio::Off {
+                        let owner = self.world.players[player].spelling;
+                        sounds.push(self.world.spellings[owner].word.to_owned());
+                    }
+                    self.play_audios(sounds);
+                    self.message = match self.play_mode {
+                        PlayMode::Solo => String::new(),
+                        PlayMode::SharedWord | PlayMode::VsComputer => self.shared_result(),
+                        PlayMode::OwnWords => format!("{} wins!", self.player_name(player)),
+                    };
+                    self.push(Screen::Results);
+                    break;
+                }
+                world::Event::Lost { player } => {
+                    self.play_audio("screech".to_owned());
+                    if self.play_mode != PlayMode::Solo {
+                        self.message = format!("{} is out. {} wins!", self.player_name(player), self.player_name(1 - player));
+                    }
+                    self.push(Screen::Results);
+                    break;
+                }
             }
         }
-    }*/
+    }
+
+    fn shared_result(&self) -> String {
+        let a = self.world.players[0].score;
+        let b = self.world.players[1].score;
+        if a == b {
+            format!("A draw, {} letters each!", a)
+        } else {
+            let winner = if a > b { 0 } else { 1 };
+            format!("{} wins, {} to {}!", self.player_name(winner), a.max(b), a.min(b))
+        }
+    }
 
-    fn catch_worm(&mut self, pos: Vec2) {
-        for id in self.n_chars..self.foods.len() {
+    fn player_name(&self, i: usize) -> String {
+        if self.world.players[i].ai.is_some() {
+            "The computer".to_owned()
+        } else {
+            format!("Player {}", i + 1)
+        }
+    }
 
-            let seg: Vec2 = pos - self.foods[id].pos;
-            let dist: f32 = seg.length();
-            if dist > 5.0 * TOUCH {
-                continue;
+    // steers the head toward the mouse or touch position
+    fn calc_pointer_force(&mut self, ctx: &Context) -> Vec2 {
+        let Some(target) = ctx.input(|i| i.pointer.latest_pos()) else {
+            return vec2(0.0, 0.0);
+        };
+        let target = target.to_vec2() / self.zoom() + self.camera.offset(self.view());
+        self.world.players[0].steer_toward(target)
+    }
+
+    // typed letters pick the food to go for, right or wrong
+    fn calc_typing_force(&mut self, ctx: &Context) -> Vec2 {
+        let typed: Vec<char> = ctx.input(|i| i.events.iter()
+            .filter_map(|e| match e {
+                Event::Text(t) => Some(t.to_owned()),
+                _ => None,
+            })
+            .collect::<String>()
+            .chars()
+            .filter(|c| c.is_alphabetic())
+            .collect()
+        );
+        for c in typed {
+            self.type_letter(c.to_ascii_lowercase());
+        }
+
+        let player = &self.world.players[0];
+        let target = player.target.and_then(|id| self.world.foods.iter().find(|f| f.id == id));
+        match target {
+            Some(fd) => player.steer_toward(player.worm.head.position + self.world.delta(fd.tag_pos, player.worm.head.position)),
+            None => player.steer_toward(player.worm.head.position), // wait
+        }
+    }
+
+    fn type_letter(&mut self, c: char) {
+        let owner = self.world.players[0].spelling;
+        let Some(next) = self.world.spellings[owner].next_letter() else { return };
+        let head = self.world.players[0].worm.head.position;
+        let nearest = |foods: Vec<&Food>| foods.into_iter()
+            .min_by(|a, b| (a.tag_pos - head).length().total_cmp(&(b.tag_pos - head).length()))
+            .map(|f| f.id);
+
+        // the typed letter if there is one, otherwise any food but a right one
+        let foods = &self.world.foods;
+        let mut pick = nearest(foods.iter().filter(|f| f.tag == Some(c)).collect());
+        if pick.is_none() {
+            let wrong: Vec<&Food> = foods.iter().filter(|f| f.tag != Some(next)).collect();
+            if !wrong.is_empty() {
+                let i = rand::thread_rng().gen_range(0..wrong.len());
+                pick = Some(wrong[i].id);
             }
-            if dist > TOUCH {
-                let ang = seg.angle();
-                self.foods[id].angle += ang_diff(ang, self.foods[id].angle) * 0.02;
+        }
+        self.world.players[0].target = pick;
+    }
+
+    fn input_forces(&mut self, ctx: &Context) -> Vec<Vec2> {
+        let keys_down = ctx.input( |i| i.keys_down.to_owned() );
+        let mut forces = Vec::new();
+        for i in 0..self.world.players.len() {
+            if self.world.players[i].ai.is_some() {
+                forces.push(self.world.ai_force(i));
                 continue;
             }
+            let f = match self.controls.steer {
+                SteerMode::Pointer if i == 0 => self.calc_pointer_force(ctx),
+                SteerMode::Typing if i == 0 && self.pads.len() == 1 => self.calc_typing_force(ctx),
+                SteerMode::Analog => self.world.players[i].calc_analog_force(&self.pads[i], &keys_down),
+                _ => self.world.players[i].calc_input_force(&self.pads[i], &keys_down),
+            };
+            forces.push(f);
+        }
+        forces
+    }
 
-            let unit = self.worm.units.pop().unwrap();
-            self.foods[id].tag = Some(unit.letter);
-            let _ = &self.foods[0..id+1].rotate_right(1);
-            if self.n_chars == 3 {
-                let lett = self.foods[3].tag;
-                self.foods[3].tag = None;
-                self.char_stack.push(lett.unwrap());
-            } else {
-                self.n_chars += 1;
-            }
-            if self.worm.units.len() < 2 {
-                self.game_state = GameState::GameOverUI;
-                self.play_audio("screech".to_owned());
-                self.worm.head.velocity = vec2(0.0, 0.0);
-                break;
+    fn join_server(&mut self) {
+        match Connection::connect(self.server_addr.trim()) {
+            Ok(mut conn) => {
+                // the server steers with our ramp
+                let _ = conn.send(ClientMsg::Ramp(self.controls.ramp));
+                self.net = Some(conn);
+                self.net_status.clear();
+                self.debug.forces.clear(); // the server knows them
+                self.world = World::default();
+                self.message = "Waiting for the server...".to_owned();
+                self.push(Screen::Play);
+                self.push(Screen::Results);
             }
+            Err(e) => self.net_status = format!("Cannot join {}: {}", self.server_addr, e),
         }
     }
 
-    fn handling_caught(&mut self, idx: usize) {
-        if self.foods[idx].tag == self.foods[0].tag {
-            self.play_audio("bite".to_owned());
-            self.worm.grow(self.foods[idx].tag.unwrap());
-            let new_lett = self.char_stack.pop();
-            self.foods[idx].tag = new_lett;
-            if idx != 0 {
-                self.foods.swap(0, idx);
+    // on a server the keys and pointer are sent and the world comes back
+    fn net_step(&mut self, ctx: &Context) {
+        let playing = self.screen() == Screen::Play;
+        let (zoom, view) = (self.zoom(), self.view());
+        let Some(conn) = &mut self.net else { return };
+        if playing {
+            let msg = match (self.controls.steer, ctx.input(|i| i.pointer.latest_pos())) {
+                (SteerMode::Pointer, Some(p)) => ClientMsg::Point(p.to_vec2() / zoom + self.camera.offset(view)),
+                _ => ClientMsg::Input(ctx.input(|i| self.controls.movement(&i.keys_down))),
+            };
+            let _ = conn.send(msg);
+        }
+
+        let lines = match conn.poll() {
+            Ok(lines) => lines,
+            Err(_) => {
+                self.net = None;
+                self.net_status = "The server has gone.".to_owned();
+                self.back_to_menu();
+                return;
+            }
+        };
+        for line in lines {
+            let (kind, rest) = line.split_once(' ').unwrap_or((&line, ""));
+            match kind {
+                "WELCOME" => {
+                    let mut f = rest.split_whitespace().map(|s| s.parse::<f32>().unwrap_or(0.0));
+                    self.me = f.next().unwrap_or(0.0) as usize;
+                    self.world.size = vec2(f.next().unwrap_or(0.0), f.next().unwrap_or(0.0));
+                }
+                "STATE" => net::apply_state(&mut self.world, &line),
+                "LEVEL" => self.world.obstacles = net::parse_level(&line),
+                "WORD" => {
+                    self.world.spellings = vec![Spelling::new(rest)];
+                    self.message.clear();
+                    if self.screen() == Screen::Results {
+                        self.pop();
+                    }
+                    self.play_words();
+                }
+                "EVENT" => match rest.split_once(' ') {
+                    Some(("CAUGHT", c)) => {
+                        let mut sounds = vec!["bite".to_owned()];
+                        sounds.extend(c.chars().next().and_then(|c| self.letter_audio.file(c)));
+                        self.play_audios(sounds);
+                    }
+                    Some(("POWER", _)) => self.play_audio("bite".to_owned()),
+                    _ => self.play_audio("oops".to_owned()),
+                },
+                "OVER" => {
+                    self.play_audio("cheering".to_owned());
+                    self.message = rest.to_owned();
+                    if self.screen() == Screen::Play {
+                        self.push(Screen::Results);
+                    }
+                }
+                _ => {}
             }
-            let pos = self.rand_vec2(self.canvas_size);
-            self.foods[0].pos = pos;
-            self.foods[0].tag_pos = Vec2 {
-                x: pos.x - FOOD_LEN,
-                y: pos.y,
+        }
+    }
+
+    // the whole world in a corner: foods, predators, worms and the view
+    fn paint_minimap(&self, painter: &egui::Painter, offset: Vec2) {
+        let width = 200.0;
+        let scale = width / self.world.size.x;
+        let size = self.world.size * scale;
+        let rect = Rect::from_min_size(pos2(self.canvas_size.x - size.x - 20.0, self.canvas_size.y - size.y - 20.0), size);
+        let at = |p: Vec2| rect.min + p * scale;
+
+        painter.rect_filled(rect, 4.0, Color32::from_black_alpha(160));
+        for fd in &self.world.foods {
+            match fd.tag {
+                Some(_) => painter.circle_filled(at(fd.pos), 2.5, LETTER_COLORS[fd.owner % LETTER_COLORS.len()]),
+                None => painter.circle_filled(at(fd.pos), 3.0, Color32::GREEN),
             };
-            let _ = &self.foods[0..self.n_chars].rotate_left(1);
-            if new_lett == None { self.n_chars -= 1; }
-            if self.n_chars == 0 { // winning
-                self.play_audio("cheering".to_owned());
-                self.game_state = GameState::GameOverUI;
+        }
+        for fd in &self.world.powers {
+            if let Some(power) = fd.power {
+                painter.circle_filled(at(fd.pos), 3.0, power.color());
+            }
+        }
+        for (i, p) in self.world.players.iter().enumerate() {
+            painter.circle_filled(at(p.worm.head.position), 3.5, PALETTES[i % PALETTES.len()][0]);
+        }
+        let view = Rect::from_min_size(at(offset), self.view() * scale).intersect(rect);
+        painter.rect_stroke(view, 0.0, Stroke::new(1.0, WHITE));
+        painter.rect_stroke(rect, 4.0, Stroke::new(1.0, Color32::GRAY));
+    }
+
+    // the power-ups in effect on the local worms, with the time they have
+    // left
+    fn paint_effects(&self, painter: &egui::Painter) {
+        let mut pos = pos2(0.5 * self.canvas_size.x, 16.0);
+        for i in self.local_players() {
+            for (power, frames) in &self.world.players[i].effects {
+                let txt = format!("{} {}s", power.label(), frames.div_ceil(60));
+                painter.text(pos, Align2::CENTER_TOP, txt, FontId::proportional(20.0), power.color());
+                let full = Rect::from_min_size(pos + vec2(-60.0, 24.0), vec2(120.0, 4.0));
+                let left = *frames as f32 / power.frames() as f32;
+                painter.rect_filled(full, 2.0, Color32::from_black_alpha(160));
+                painter.rect_filled(Rect::from_min_size(full.min, vec2(left * full.width(), 4.0)), 2.0, power.color());
+                pos.y += 34.0;
             }
         }
     }
 
     fn play_audio(&mut self, s: String) {
-        let s = format!("sounds/{}.wav", s);
-        thread::spawn(move|| {
-            let mut snd = Sound::new(&s).unwrap();
-            snd.play();
-            while snd.is_playing() {}
-        });
+        self.audio.play(&s);
+    }
+
+    fn play_audios(&mut self, names: Vec<String>) {
+        self.audio.play_all(names);
+    }
+
+    // a built-in vocabulary or a pack, by name
+    fn choose_vocab(&mut self, name: &str) {
+        if let Some((_, title, words)) = VOCABS.iter().find(|v| v.0 == name) {
+            self.vocabulary = words.iter().map(|s| s.to_string()).collect();
+            self.emojis.clear();
+            self.audio.use_pack(None);
+            self.play_audio(title.to_string());
+        } else if let Some((_, path)) = self.packs.iter().find(|p| p.0 == name).cloned() {
+            self.load_pack(&path);
+        }
+    }
+
+    fn load_pack(&mut self, path: &Path) {
+        let pack = match Pack::read(path) {
+            Ok(pack) => pack,
+            Err(e) => {
+                eprintln!("cannot load {}: {}", path.display(), e);
+                return;
+            }
+        };
+        if pack.meta.words.is_empty() {
+            eprintln!("{} has no words", path.display());
+            return;
+        }
+        let dir = std::env::temp_dir().join("caterpillar").join(pack.dir_name(path));
+        match pack.extract(&dir) {
+            Ok(sounds) => self.audio.use_pack(Some(sounds)),
+            Err(e) => {
+                eprintln!("cannot unpack {}: {}", path.display(), e);
+                self.audio.use_pack(None);
+            }
+        }
+        self.vocabulary = pack.meta.words.iter().map(|(w, _)| w.to_owned()).collect();
+        self.emojis = pack.meta.words.into_iter()
+            .filter_map(|(w, e)| e.map(|e| (w, e)))
+            .collect();
+        if let Some(title) = pack.meta.title {
+            self.play_audio(title);
+        }
     }
 
     fn center_widgets(&mut self, ui: &mut Ui) {
@@ -275,6 +628,131 @@ impl Game {
         let txt = RichText::new(self.vocabulary.join(", ")).color(Color32::GREEN).size(20.);
         ui.label(txt);
         ui.add_space(40.);
+        self.letter_audio_widgets(ui);
+        ui.add_space(40.);
+        self.level_widgets(ui);
+        ui.add_space(10.);
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("Arena:").size(20.).color(WHITE));
+            for a in ARENA_SIZES {
+                ui.radio_value(&mut self.arena, a, RichText::new(a.label()).size(20.));
+            }
+        });
+        ui.add_space(40.);
+        self.play_mode_widgets(ui);
+        ui.add_space(40.);
+        self.body_widgets(ui);
+    }
+
+    // the body model of the worms and its numbers, also while paused
+    fn body_widgets(&mut self, ui: &mut Ui) {
+        let txt = RichText::new("Body:").color(Color32::RED).size(22.);
+        ui.label(txt);
+        ui.add_space(10.);
+        ui.horizontal(|ui| {
+            for i in 0..self.world.bodies.len() {
+                let txt = RichText::new(self.world.bodies[i].name()).size(20.);
+                if ui.radio(self.world.body == i, txt).clicked() {
+                    self.world.body = i;
+                    for p in &mut self.world.players {
+                        p.worm.body = i;
+                        p.worm.head.color = p.worm.color();
+                        p.worm.neck.color = p.worm.color();
+                    }
+                }
+            }
+        });
+        for p in self.world.bodies[self.world.body].params() {
+            ui.add(Slider::new(p.value, p.range).text(RichText::new(p.name).size(18.)));
+        }
+        let txt = RichText::new("Stiff body: links keep their length").size(20.);
+        if ui.checkbox(&mut self.constraints, txt).changed() {
+            self.world.constraints = self.constraints;
+        }
+    }
+
+    fn level_widgets(&mut self, ui: &mut Ui) {
+        let txt = RichText::new("Level:").color(Color32::RED).size(22.);
+        ui.label(txt);
+        ui.add_space(10.);
+        ui.horizontal_wrapped(|ui| {
+            for i in 0..self.levels.len() {
+                let txt = RichText::new(&self.levels[i].name).size(20.);
+                if ui.radio(self.level == i, txt).clicked() {
+                    self.choose_level(i);
+                }
+            }
+        });
+    }
+
+    // a level may come with its own vocabulary and difficulty
+    fn choose_level(&mut self, i: usize) {
+        self.level = i;
+        let level = self.levels[i].clone();
+        if let Some(vocab) = &level.vocab {
+            self.choose_vocab(vocab);
+        }
+        if let Some(skill) = level.difficulty {
+            self.skill = skill;
+        }
+    }
+
+    fn vocab_names(&self) -> Vec<String> {
+        VOCABS.iter().map(|v| v.0.to_owned())
+            .chain(self.packs.iter().map(|p| p.0.to_owned()))
+            .collect()
+    }
+
+    fn play_mode_widgets(&mut self, ui: &mut Ui) {
+        let txt = RichText::new("Players:").color(Color32::RED).size(22.);
+        ui.label(txt);
+        ui.add_space(10.);
+        ui.horizontal(|ui| {
+            for m in PLAY_MODES {
+                ui.radio_value(&mut self.play_mode, m, RichText::new(m.label()).size(20.));
+            }
+        });
+        if self.play_mode == PlayMode::VsComputer {
+            ui.add_space(10.);
+            ui.horizontal(|ui| {
+                ui.label(RichText::new("Computer:").size(20.).color(WHITE));
+                for k in SKILLS {
+                    ui.radio_value(&mut self.skill, k, RichText::new(k.label()).size(20.));
+                }
+            });
+        } else if self.play_mode != PlayMode::Solo {
+            ui.add_space(10.);
+            let txt = "Player 1 steers with the arrow keys, player 2 with WASD. \
+                       Touch the last letter of the other worm to steal it.";
+            ui.label(RichText::new(txt).size(18.).color(WHITE));
+        }
+        ui.add_space(10.);
+        let txt = RichText::new("Bumping into yourself costs the last letter").size(20.);
+        ui.checkbox(&mut self.self_penalty, txt);
+        let txt = RichText::new("Wrap around: leave one edge, come back at the other").size(20.);
+        ui.checkbox(&mut self.wrap, txt);
+        ui.add_space(20.);
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("Server:").size(20.).color(WHITE));
+            ui.text_edit_singleline(&mut self.server_addr);
+            if ui.button(RichText::new("Join").size(20.)).clicked() {
+                self.join_server();
+            }
+        });
+        if !self.net_status.is_empty() {
+            ui.label(RichText::new(&self.net_status).size(18.).color(Color32::YELLOW));
+        }
+    }
+
+    fn letter_audio_widgets(&mut self, ui: &mut Ui) {
+        let txt = RichText::new("Letter Audio:").color(Color32::RED).size(22.);
+        ui.label(txt);
+        ui.add_space(10.);
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.letter_audio, LetterAudio::Off,   RichText::new("Off").size(20.));
+            ui.radio_value(&mut self.letter_audio, LetterAudio::Name,  RichText::new("Letter names").size(20.));
+            ui.radio_value(&mut self.letter_audio, LetterAudio::Sound, RichText::new("Letter sounds").size(20.));
+        });
     }
 
     fn side_widgets(&mut self, ui: &mut Ui) {
@@ -285,25 +763,39 @@ impl Game {
         ui.label(RichText::new("letters to form a word.").size(18.0).color(WHITE));
         ui.add_space(50.0);
         ui.vertical(|ui| {
-            ui.label(RichText::new("Space  -> pause").size(18.0).color(Color32::GREEN));
-            ui.label(RichText::new("Escape -> quit").size(18.0).color(Color32::GREEN));
-            ui.label(RichText::new("P      -> play audio").size(18.0).color(Color32::GREEN));
-            ui.label(RichText::new("R      -> reset").size(18.0).color(Color32::GREEN));
-            ui.label(RichText::new("F1     -> toggle").size(18.0).color(Color32::GREEN));
+            for a in [Action::Pause, Action::Quit, Action::PlayAudio, Action::Reset, Action::Toggle, Action::Tuning, Action::Debug] {
+                let txt = format!("{:<10} -> {}", self.controls.key(a).name(), a.label());
+                ui.label(RichText::new(txt).size(18.0).color(Color32::GREEN));
+            }
         });
         ui.add_space(50.0);
         ui.vertical(|ui| {
-            ui.label(RichText::new("ArrowUp    -> forward").size(18.0).color(WHITE));
-            ui.label(RichText::new("ArrowLeft  -> left").size(18.0).color(WHITE));
-            ui.label(RichText::new("ArrowRight -> right").size(18.0).color(WHITE));
-            ui.label(RichText::new("ArrowDown  -> break").size(18.0).color(WHITE));
+            for a in [Action::Forward, Action::Left, Action::Right, Action::Brake] {
+                let txt = format!("{:<10} -> {}", self.controls.key(a).name(), a.label());
+                ui.label(RichText::new(txt).size(18.0).color(WHITE));
+            }
+        });
+        ui.add_space(20.0);
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("Steer with:").size(18.0).color(WHITE));
+            for m in STEER_MODES {
+                ui.radio_value(&mut self.controls.steer, m, RichText::new(m.label()).size(18.0));
+            }
+        });
+        ui.horizontal(|ui| {
+            if ui.button(RichText::new("Controls...").size(18.0)).clicked() {
+                self.push(Screen::Controls);
+            }
+            if ui.button(RichText::new("Level editor...").size(18.0)).clicked() {
+                self.push(Screen::Editor);
+            }
         });
 
         ui.add_space(100.0);
         ui.horizontal(|ui| {
             let text = RichText::new("Start").text_style(HEADING);
             if ui.add_sized([150., 60.], Button::new(text)).clicked() {
-                self.game_state = GameState::Init;
+                self.push(Screen::Play);
             }
             ui.add_space(5.0);
             let text = RichText::new("Quit").text_style(HEADING);
@@ -313,193 +805,185 @@ impl Game {
             ui.add_space(5.0);
         });
     }
+}
 
-    fn calc_input_force(&mut self, ctx: &Context) -> Vec2 {
-        let keys_down = ctx.input( |i| i.keys_down.to_owned() );
-
-        if keys_down.is_empty() {
-            self.forward_f = 0.0;
-            self.left_f    = 0.0;
-            self.right_f   = 0.0;
-            return vec2(0.0, 0.0);
-        }
 
-        let ang = (self.worm.head.position - self.worm.neck.position).angle();
-
-        if keys_down.contains(&Key::ArrowUp) {
-            self.forward_f += 0.01;
-            self.forward_f = self.forward_f.min(MAX_FORWARD);
-            self.left_f    = 0.0;
-            self.right_f   = 0.0;
-            return vec2(
-                (self.forward_f+0.05) * ang.cos(),
-                (self.forward_f+0.05) * ang.sin()
-            );
-        }
-
-        if keys_down.contains(&Key::ArrowRight) {
-            self.worm.head.velocity = 0.99 * self.worm.head.velocity;
-            self.left_f    = 0.0;
-            self.forward_f = 0.0;
-            self.right_f  += 0.01;
-            self.right_f   = self.right_f.min(MAX_TURN);
-            return vec2(
-                -(self.right_f+0.2) * ang.sin(),
-                 (self.right_f+0.2) * ang.cos()
-            );
-        }
-
-        if keys_down.contains(&Key::ArrowLeft) {
-            self.worm.head.velocity = 0.99 * self.worm.head.velocity;
-            self.right_f   = 0.0;
-            self.forward_f = 0.0;
-            self.left_f   += 0.01;
-            self.left_f    = self.left_f.min(MAX_TURN);
-            return vec2(
-                 (self.left_f+0.2) * ang.sin(),
-                -(self.left_f+0.2) * ang.cos()
-            );
-        }
-
-        if keys_down.contains(&Key::ArrowDown) {
-            self.worm.head.velocity = 0.95 * self.worm.head.velocity;
-            self.forward_f = 0.0;
-            self.left_f    = 0.0;
-            self.right_f   = 0.0;
-            return vec2(0.0, 0.0);
-        }
 
-        // other keys
-        self.left_f    = 0.0;
-        self.right_f   = 0.0;
-        self.forward_f = 0.0;
-        vec2(0.0, 0.0)
-    }
+impl eframe::App for Game {
 
-    fn rand_vec2(&mut self, canvas: Vec2) -> Vec2 {
-        let mut rng = rand::thread_rng();
-        let mut pos = Vec2 { x: 0.0, y: 0.0 };
-        for _ in 0..10 { // try ten times
-            pos = vec2 (
-                rng.gen_range(MIN_DIST..canvas.x-MIN_DIST),
-                rng.gen_range(MIN_DIST..canvas.y-MIN_DIST),
-            );
-            let mut overlap = false;
-            for fd in &self.foods {
-                if (fd.pos - pos).length() < 2.0 * MIN_DIST {
-                    overlap = true;
-                    break;
-                }
+    fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
+        ctx.request_repaint();
+        self.canvas_size = ctx.screen_rect().size();
+        self.apply_changes();
+
+        match self.screen() {
+            Screen::Menu => {
+                SidePanel::left("my_left_panel").show(ctx, |ui| {
+                    self.side_widgets(ui);
+                });
+                CentralPanel::default().show(ctx, |ui| {
+                    self.center_widgets(ui);
+                });
+                return;
             }
-
-            if overlap == false {
-                if (self.worm.head.position - pos).length() < 2.0 * MIN_DIST {
-                    overlap = true;
-                }
+            Screen::Controls => {
+                CentralPanel::default().show(ctx, |ui| {
+                    self.controls_ui(ui);
+                });
+                return;
             }
-
-            if overlap == false {
-                break;
+            Screen::Editor => {
+                let vocabs = self.vocab_names();
+                let Some(editor) = &mut self.editor else { return };
+                if editor.show(ctx, &vocabs) {
+                    self.pop();
+                }
+                return;
             }
+            Screen::Play | Screen::Pause | Screen::Results => {}
         }
-        return pos;
-    }
-}
-
-
 
-impl eframe::App for Game {
-
-    fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
-        ctx.request_repaint();
+        if self.controls.pressed(ctx, Action::Quit) {
+            ctx.send_viewport_cmd(ViewportCommand::Close);
+        }
 
-        if self.game_state == GameState::StartUI {
-            SidePanel::left("my_left_panel").show(ctx, |ui| {
-                self.side_widgets(ui);
-            });
-            CentralPanel::default().show(ctx, |ui| {
-                self.center_widgets(ui);
-            });
-            return;
+        if self.controls.pressed(ctx, Action::Reset) {
+            self.back_to_menu();
         }
 
-        if ctx.input(|i| i.key_pressed(Key::Escape)) {
-            ctx.send_viewport_cmd(ViewportCommand::Close);
+        if self.controls.pressed(ctx, Action::Tuning) {
+            self.tuning_open = !self.tuning_open;
         }
 
-        if ctx.input(|i| i.key_pressed(Key::R)) {
-            self.reset();
+        if self.controls.pressed(ctx, Action::Debug) {
+            self.debug_open = !self.debug_open;
         }
+        self.debug.frame(ctx.input(|i| i.unstable_dt));
 
-        if self.game_state == GameState::Play {
-            if ctx.input(|i| i.key_pressed(Key::Space)) {
-                self.paused = !self.paused;
+        if self.controls.pressed(ctx, Action::Pause) {
+            match self.screen() {
+                Screen::Play  => self.push(Screen::Pause),
+                Screen::Pause => self.pop(),
+                _ => {}
             }
+        }
 
-            if ctx.input(|i| i.key_pressed(Key::P)) {
-                self.play_audio(self.word.to_owned());
+        if self.screen() == Screen::Play {
+            if self.controls.pressed(ctx, Action::PlayAudio) {
+                self.play_words();
             }
 
-            if ctx.input(|i| i.key_pressed(Key::F1)) {
-                self.worm.soft_mode = !self.worm.soft_mode;
-                if self.worm.soft_mode {
-                    self.worm.head.color = PURPLE1;
-                    self.worm.neck.color = PURPLE1;
-                } else {
-                    self.worm.head.color = Color32::DARK_RED;
-                    self.worm.neck.color = Color32::DARK_RED;
+            if self.controls.pressed(ctx, Action::Toggle) {
+                for p in &mut self.world.players {
+                    p.worm.toggle_mode(self.world.bodies.len());
                 }
             }
 
-            if !self.paused {
-                let f: Vec2 = self.calc_input_force(&ctx);
-                self.worm.drive_me(f);
-                self.worm.cross_border(self.canvas_size);
-
-                for fd in &mut self.foods  {
-                    fd.move_me(self.canvas_size);
+            if self.net.is_none() {
+                // tuning takes effect at once
+                for p in &mut self.world.players {
+                    self.tuning.apply(&mut p.worm);
                 }
-                for i in 0..FIVE-1 {
-                    for j in i+1..FIVE {
-                        self.avoid(i, j);
-                    }
+                for pad in &mut self.pads {
+                    pad.ramp = self.controls.ramp;
                 }
-
-                if let Some(idx) = self.find_food() {
-                    self.handling_caught(idx);
-                } else {
-                    let pos = self.worm.units[self.worm.units.len()-1].position;
-                    self.catch_worm(pos);
+                let forces = self.input_forces(ctx);
+                if self.arena == ArenaSize::Window {
+                    self.world.size = self.canvas_size;
                 }
+                self.world.frames = 60.0 * ctx.input(|i| i.unstable_dt);
+                self.world.step(&forces);
+                self.debug.forces = forces;
+                self.handle_events();
             }
         } // end of Play
 
+        self.net_step(ctx);
+
+        if self.tuning_open {
+            SidePanel::right("tuning").show(ctx, |ui| {
+                ScrollArea::vertical().show(ui, |ui| self.tuning_panel(ui));
+            });
+        }
 
         CentralPanel::default().show(ctx, |ui| {
-            let avail_size = ui.available_size();
-            if avail_size != self.canvas_size {
-                self.canvas_size = avail_size;
+            if self.screen() == Screen::Results {
+                self.game_over_ui(ui);
             }
 
-            if self.game_state == GameState::Init {
-                self.worm.reset();
-                self.choose_word();
-                self.create_foods();
-                self.play_audio(self.word.to_owned());
-                self.game_state = GameState::Play;
-                return;
+            // the world is painted on its own layer, moved by the camera
+            let view = self.view();
+            let focus = self.focus();
+            self.camera.follow(focus, view, self.world.size);
+            let offset = self.camera.offset(view);
+            let layer = LayerId::new(Order::Middle, Id::new("world"));
+            let transform = TSTransform::from_scaling(self.zoom()) * TSTransform::from_translation(-offset);
+            ui.ctx().set_transform_layer(layer, transform);
+            let painter = ui.painter().clone()
+                .with_layer_id(layer)
+                .with_clip_rect(Rect::from_min_size(Pos2::ZERO, self.world.size));
+
+            for o in &self.world.obstacles {
+                o.paint(&painter);
             }
-
-            if self.game_state == GameState::GameOverUI {
-                self.game_over_ui(ui);
+            for p in &mut self.world.players {
+                p.worm.paint(&painter);
+            }
+            let target = self.world.players.first().and_then(|p| p.target);
+            for fd in &mut self.world.foods {//&mut 
+                fd.paint(&painter);
+                if self.controls.steer == SteerMode::Typing && target == Some(fd.id) {
+                    painter.circle_stroke(fd.tag_pos.to_pos2(), DIAMETER, Stroke::new(2.0, Color32::YELLOW));
+                }
+            }
+            for fd in &mut self.world.powers {
+                fd.paint(&painter);
+            }
+            for p in &self.world.players {
+                if p.has(Power::Shield) {
+                    painter.circle_stroke(p.worm.head.position.to_pos2(), R_LEAD + 6.0, Stroke::new(3.0, Power::Shield.color()));
+                }
+            }
+            for i in self.local_players() {
+                if !self.world.players[i].has(Power::Hint) {
+                    continue;
+                }
+                for fd in self.world.next_foods(i) {
+                    painter.circle_stroke(fd.tag_pos.to_pos2(), DIAMETER, Stroke::new(3.0, Power::Hint.color()));
+                }
+            }
+            if (self.world.size.x > view.x || self.world.size.y > view.y) && !self.world.wrap {
+                painter.rect_stroke(Rect::from_min_size(Pos2::ZERO, self.world.size), 0.0, Stroke::new(2.0, Color32::GRAY));
             }
 
-            let painter = ui.painter();
-            self.worm.paint(painter);
-            for fd in &mut self.foods {//&mut 
-                fd.paint(painter);
+            let hud = ui.ctx().layer_painter(LayerId::new(Order::Foreground, Id::new("hud")));
+            if let Some(emoji) = self.world.spellings.first().and_then(|s| self.emojis.get(&s.word)) {
+                let pos = pos2(self.canvas_size.x - 60.0, 60.0);
+                hud.text(pos, Align2::CENTER_CENTER, emoji, FontId::proportional(64.0), WHITE);
+            }
+            if self.play_mode != PlayMode::Solo || self.net.is_some() {
+                for (i, p) in self.world.players.iter().enumerate() {
+                    let pos = pos2(20.0, 20.0 + 30.0 * i as f32);
+                    let txt = format!("{}: {}", self.player_name(i), p.score);
+                    hud.text(pos, Align2::LEFT_TOP, txt, FontId::proportional(24.0), PALETTES[i % PALETTES.len()][0]);
+                }
+            }
+            if self.world.size.x > view.x || self.world.size.y > view.y {
+                self.paint_minimap(&hud, offset);
+            }
+            self.paint_effects(&hud);
+            if self.debug_open {
+                // the step has moved and dropped foods since it filled the
+                // grid, and the foods from a server were never in it
+                self.world.index_foods();
+                self.debug.paint_world(&painter, &self.world);
+                self.debug.paint_hud(&hud, ctx.screen_rect());
             }
         });
+
+        if self.screen() == Screen::Pause {
+            self.pause_ui(ctx);
+        }
     }
 }
 
@@ -510,71 +994,251 @@ impl Game {
         ui.label(RichText::new("Select Vocabs (Default: Zoo Animals):").size(20.0).color(WHITE));
         ui.add_space(10.0);
         Grid::new("some_unique_id").show(ui, |ui| {
-            if button(ui, "Zoo Animals").clicked() {
-                self.vocabulary = ZOO_ANIMALS.iter().map(|s| s.to_string()).collect();
-                self.play_audio("zoo-animals".to_owned());
-            }
-            if button(ui, "Sea Animals").clicked() {
-                self.vocabulary = SEA_ANIMALS.iter().map(|s| s.to_string()).collect();
-                self.play_audio("sea-animals".to_owned());
+            for (i, (name, _, _)) in VOCABS.iter().enumerate() {
+                if button(ui, name).clicked() {
+                    self.choose_vocab(name);
+                }
+                if i % 2 == 1 {
+                    ui.end_row();
+                }
             }
-            ui.end_row();
 
-            if button(ui, "Birds").clicked() {
-                self.vocabulary = BIRDS.iter().map(|s| s.to_string()).collect();
-                self.play_audio("birds".to_owned());
+            let packs = self.packs.clone();
+            for (i, (name, _)) in packs.iter().enumerate() {
+                if button(ui, name).clicked() {
+                    self.choose_vocab(name);
+                }
+                if i % 2 == 1 {
+                    ui.end_row();
+                }
             }
-            if button(ui, "Insects").clicked() {
-                self.vocabulary = INSECTS.iter().map(|s| s.to_string()).collect();
-                self.play_audio("insects".to_owned());
+        });
+    }
+
+    fn controls_ui(&mut self, ui: &mut Ui) {
+        if let Some(action) = self.rebinding {
+            let key = ui.input(|i| i.events.iter().find_map(|e| match e {
+                Event::Key { key, pressed: true, .. } => Some(*key),
+                _ => None,
+            }));
+            if let Some(key) = key {
+                if key != Key::Escape || action == Action::Quit {
+                    self.controls.set_key(action, key);
+                }
+                self.rebinding = None;
             }
-            ui.end_row();
+        }
 
-            if button(ui, "Farm Animals").clicked() {
-                self.vocabulary = FARM_ANIMALS.iter().map(|s| s.to_string()).collect();
-                self.play_audio("farm-animals".to_owned());
+        ui.label(RichText::new("Controls").size(28.0).color(WHITE));
+        ui.add_space(20.0);
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("Steer with:").size(20.).color(Color32::RED));
+            for m in STEER_MODES {
+                ui.radio_value(&mut self.controls.steer, m, RichText::new(m.label()).size(20.));
             }
-            if button(ui, "Body Parts").clicked() {
-                self.vocabulary = BODY_PARTS.iter().map(|s| s.to_string()).collect();
-                self.play_audio("body-parts".to_owned());
+        });
+        ui.add_space(10.0);
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("Keys:").size(20.).color(Color32::RED));
+            for s in [Scheme::Arrows, Scheme::Wasd, Scheme::OneHanded, Scheme::Custom] {
+                let selected = self.controls.scheme == s;
+                if ui.radio(selected, RichText::new(s.label()).size(20.)).clicked() {
+                    self.controls.set_scheme(s);
+                }
+            }
+        });
+        ui.add_space(20.0);
+
+        Grid::new("key_bindings").spacing([40.0, 8.0]).show(ui, |ui| {
+            for a in ACTIONS {
+                ui.label(RichText::new(a.label()).size(20.0).color(WHITE));
+                let key = if self.rebinding == Some(a) {
+                    RichText::new("press a key...").size(20.0).color(Color32::YELLOW)
+                } else {
+                    RichText::new(self.controls.key(a).name()).size(20.0).color(Color32::GREEN)
+                };
+                ui.label(key);
+                if ui.button(RichText::new("Change").size(18.0)).clicked() {
+                    self.rebinding = Some(a);
+                }
+                ui.end_row();
             }
+        });
+        ui.add_space(30.0);
+
+        ui.label(RichText::new("Key ramp (how held keys build up force)").size(22.0).color(Color32::RED));
+        ui.add_space(10.0);
+        let r = &mut self.controls.ramp;
+        Grid::new("key_ramp").spacing([40.0, 8.0]).show(ui, |ui| {
+            ui.label("forward start");
+            ui.add(Slider::new(&mut r.forward_base, 0.0..=0.3));
+            ui.end_row();
+            ui.label("forward step per frame");
+            ui.add(Slider::new(&mut r.forward_step, 0.001..=0.05));
             ui.end_row();
+            ui.label("forward maximum");
+            ui.add(Slider::new(&mut r.max_forward, 0.05..=0.5));
+            ui.end_row();
+            ui.label("turn start");
+            ui.add(Slider::new(&mut r.turn_base, 0.0..=1.0));
+            ui.end_row();
+            ui.label("turn step per frame");
+            ui.add(Slider::new(&mut r.turn_step, 0.001..=0.05));
+            ui.end_row();
+            ui.label("turn maximum");
+            ui.add(Slider::new(&mut r.max_turn, 0.2..=3.0));
+            ui.end_row();
+            ui.label("release delay (frames)");
+            ui.add(Slider::new(&mut r.release_frames, 0..=60));
+            ui.end_row();
+            ui.label("analog spin step per frame");
+            ui.add(Slider::new(&mut r.spin_step, 0.005..=0.5));
+            ui.end_row();
+            ui.label("analog spin maximum");
+            ui.add(Slider::new(&mut r.max_spin, 0.01..=0.2));
+            ui.end_row();
+            ui.label("analog curve");
+            ui.add(Slider::new(&mut r.curve, 0.5..=4.0));
+            ui.end_row();
+        });
+        ui.add_space(30.0);
 
-            if button(ui, "Fruits").clicked() {
-                self.vocabulary = FRUITS.iter().map(|s| s.to_string()).collect();
-                self.play_audio("fruits".to_owned());
+        ui.horizontal(|ui| {
+            if button(ui, "Defaults").clicked() {
+                self.controls = Controls::from_scheme(self.controls.scheme);
             }
-            if button(ui, "Vegetables").clicked() {
-                self.vocabulary = VEGETABLES.iter().map(|s| s.to_string()).collect();
-                self.play_audio("vegetables".to_owned());
+            if button(ui, "Done").clicked() {
+                self.pop();
             }
-            ui.end_row();
+        });
+    }
+
+    fn tuning_panel(&mut self, ui: &mut Ui) {
+        ui.label(RichText::new("Tuning").size(22.).color(Color32::RED));
+        ui.add_space(10.);
+        for (key, p) in self.tunables() {
+            if !key.contains('.') {
+                ui.add(Slider::new(p.value, p.range).text(p.name));
+            }
+        }
+        ui.add_space(20.);
+        self.body_widgets(ui); // and the numbers of the body model
+        ui.add_space(20.);
 
-            if button(ui, "Food and Drinks").clicked() {
-                self.vocabulary = FOOD_AND_DRINKS.iter().map(|s| s.to_string()).collect();
-                self.play_audio("food-and-drinks".to_owned());
+        ui.label(RichText::new("Presets").size(22.).color(Color32::RED));
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut self.preset_name);
+            let name = self.preset_name.trim().to_owned();
+            if ui.add_enabled(!name.is_empty(), Button::new("Save")).clicked() {
+                let values = self.tunables().into_iter().map(|(k, p)| (k, *p.value)).collect();
+                tuning::save_preset(&name, values);
+                self.presets = tuning::load_presets();
             }
-            if button(ui, "Sport and Games").clicked() {
-                self.vocabulary = SPORT_AND_GAMES.iter().map(|s| s.to_string()).collect();
-                self.play_audio("sport-and-games".to_owned());
+        });
+        let mut chosen = None;
+        ui.horizontal_wrapped(|ui| {
+            if ui.button("Defaults").clicked() {
+                self.tuning = Tuning::default();
+                self.world.bodies = body::models();
+                self.controls.ramp.max_forward = MAX_FORWARD;
+                self.controls.ramp.max_turn = MAX_TURN;
+            }
+            for (i, (name, _)) in self.presets.iter().enumerate() {
+                if ui.button(name).clicked() {
+                    chosen = Some(i);
+                }
             }
         });
+        if let Some(i) = chosen {
+            let (name, values) = self.presets[i].clone();
+            for (key, p) in self.tunables() {
+                if let Some((_, v)) = values.iter().find(|(k, _)| *k == key) {
+                    *p.value = v.clamp(*p.range.start(), *p.range.end());
+                }
+            }
+            self.preset_name = name;
+        }
+    }
+
+    // every number the tuning panel changes, by its key in the presets file
+    fn tunables(&mut self) -> Vec<(String, Param<'_>)> {
+        let mut all: Vec<(String, Param)> = self.tuning.params().into_iter()
+            .map(|p| (tuning::key(None, p.name), p))
+            .collect();
+        let r = &mut self.controls.ramp;
+        all.push(("max_forward".to_owned(), Param { name: "max forward", value: &mut r.max_forward, range: 0.05..=0.5 }));
+        all.push(("max_turn".to_owned(),    Param { name: "max turn",    value: &mut r.max_turn,    range: 0.2..=3.0 }));
+        for b in &mut self.world.bodies {
+            let group = b.name();
+            for p in b.params() {
+                all.push((tuning::key(Some(group), p.name), p));
+            }
+        }
+        all
+    }
+
+    // drawn over the frozen game
+    fn pause_ui(&mut self, ctx: &Context) {
+        let modal = Modal::new(Id::new("pause"))
+            .backdrop_color(Color32::from_black_alpha(160))
+            .show(ctx, |ui| {
+                ui.vertical_centered(|ui| {
+                    ui.label(RichText::new("Paused").size(28.0).color(WHITE));
+                    ui.add_space(10.0);
+                    if button(ui, "Resume").clicked() {
+                        self.pop();
+                    }
+                    // a server keeps going without us
+                    if self.net.is_none() && button(ui, "Restart word").clicked() {
+                        let words = self.world.spellings.iter().map(|s| s.word.clone()).collect();
+                        self.world.restart(words);
+                        self.camera.jump(self.focus(), self.view(), self.world.size);
+                        self.play_words();
+                        self.pop();
+                    }
+                    if button(ui, "Replay word audio").clicked() {
+                        self.play_words();
+                    }
+                    if button(ui, "Settings").clicked() {
+                        self.push(Screen::Controls);
+                    }
+                    if button(ui, "Quit to menu").clicked() {
+                        self.back_to_menu();
+                    }
+                });
+                // the server moves the worms there
+                if self.net.is_none() {
+                    ui.add_space(20.);
+                    self.body_widgets(ui);
+                }
+            });
+        if modal.should_close() {
+            self.pop();
+        }
     }
 
     fn game_over_ui(&mut self, ui: &mut Ui) {
+        if !self.message.is_empty() {
+            ui.label(RichText::new(&self.message).size(28.0).color(WHITE));
+            ui.add_space(10.0);
+        }
         ui.horizontal(|ui| {
-            let button = Button::new(RichText::new("Restart").text_style(HEADING));//Button
+            let button = Button::new(RichText::new("Menu").text_style(HEADING));//Button
             if ui.add_sized(Vec2{x: self.canvas_size.x/3.0, y: 40.0}, button).clicked() {
-                self.reset();
+                self.back_to_menu();
             }
             let button = Button::new(RichText::new("Quit").text_style(HEADING));
             if ui.add_sized(Vec2{x: self.canvas_size.x/3.0, y: 40.0}, button).clicked() {
                 ui.ctx().send_viewport_cmd(ViewportCommand::Close);
             }
 
-            let button = Button::new(RichText::new("Continue").text_style(HEADING));
-            if ui.add_sized(ui.available_size(), button).clicked() {
-                self.game_state = GameState::Init;
+            // on a server the next round starts by itself
+            if self.net.is_none() {
+                let button = Button::new(RichText::new("Continue").text_style(HEADING));
+                if ui.add_sized(ui.available_size(), button).clicked() {
+                    self.pop();
+                    self.switch(Screen::Play);
+                }
             }
 
         });
@@ -594,6 +1258,37 @@ pub fn configure_fonts(ctx: &Context) {
     ctx.set_fonts(fonts);
 }
 
+// the vocabulary packs found in the packs directory, by name
+fn find_packs() -> Vec<(String, PathBuf)> {
+    let mut packs = Vec::new();
+    let Ok(entries) = fs::read_dir(PACK_DIR) else { return packs };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some(pack::EXTENSION) {
+            continue;
+        }
+        if let Ok(meta) = Pack::read_meta(&path) {
+            packs.push((meta.name, path));
+        }
+    }
+    packs.sort();
+    packs
+}
+
+// the open field and the levels found in the levels directory
+fn find_levels() -> Vec<Level> {
+    let mut levels = vec![Level { name: "Open field".to_owned(), ..Default::default() }];
+    let Ok(entries) = fs::read_dir(level::DIR) else { return levels };
+    let mut found: Vec<Level> = entries.flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().and_then(|e| e.to_str()) == Some(level::EXTENSION))
+        .filter_map(|p| Level::load(&p).ok())
+        .collect();
+    found.sort_by(|a, b| a.name.cmp(&b.name));
+    levels.extend(found);
+    levels
+}
+
 fn button(ui: &mut Ui, text: &str) -> Response {
     ui.add_sized(
         Vec2{x: 280.0, y: 40.0},
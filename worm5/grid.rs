@@ -0,0 +1,79 @@
+// A uniform grid over the arena, to find what is near a point without
+// looking at everything. Things go in the cell they are in, a query looks
+// at the cells a radius around; the caller still checks the distance.
+// Positions off the arena count to the cells at its edge, or come back in
+// at the other side when the arena wraps.
+
+use egui::{Vec2, vec2};
+
+pub const CELL: f32 = 128.0; // pixels, about the reach of the foods
+
+
+pub struct Grid<T> {
+    cell:  f32,
+    cols:  usize,
+    rows:  usize,
+    size:  Vec2,
+    wrap:  bool,
+    cells: Vec<Vec<T>>,
+}
+
+impl<T: Copy> Default for Grid<T> {
+    fn default() -> Self {
+        Self { cell: CELL, cols: 0, rows: 0, size: vec2(0.0, 0.0), wrap: false, cells: Vec::new() }
+    }
+}
+
+impl<T: Copy> Grid<T> {
+
+    // empties the grid and fits it to the arena, keeping the memory
+    pub fn reset(&mut self, size: Vec2, wrap: bool) {
+        self.cols = ((size.x / self.cell).ceil() as usize).max(1);
+        self.rows = ((size.y / self.cell).ceil() as usize).max(1);
+        self.size = size;
+        self.wrap = wrap;
+        self.cells.resize_with(self.cols * self.rows, Vec::new);
+        for c in &mut self.cells {
+            c.clear();
+        }
+    }
+
+    pub fn insert(&mut self, pos: Vec2, item: T) {
+        let x = self.fit(pos.x, self.size.x, self.cols);
+        let y = self.fit(pos.y, self.size.y, self.rows);
+        if let Some(cell) = self.cells.get_mut(y * self.cols + x) {
+            cell.push(item);
+        }
+    }
+
+    // everything in the cells within radius of pos
+    pub fn near(&self, pos: Vec2, radius: f32) -> impl Iterator<Item = T> + '_ {
+        let xs = self.span(pos.x, radius, self.size.x, self.cols);
+        let ys = self.span(pos.y, radius, self.size.y, self.rows);
+        ys.flat_map(move |y| xs.clone().map(move |x| y * self.cols + x))
+            .filter(|&i| i < self.cells.len())
+            .flat_map(|i| self.cells[i].iter().copied())
+    }
+
+    // the column (or row) of x in the grid: around the arena when wrapping,
+    // the one at the edge otherwise
+    fn fit(&self, x: f32, extent: f32, n: usize) -> usize {
+        let x = if self.wrap && extent > 0.0 { x.rem_euclid(extent) } else { x };
+        ((x / self.cell).floor().max(0.0) as usize).min(n.max(1) - 1)
+    }
+
+    // the columns (or rows) from x - radius to x + radius, each once; when
+    // wrapping, a span over the edge of the arena goes on at the other side
+    fn span(&self, x: f32, radius: f32, extent: f32, n: usize) -> impl Iterator<Item = usize> + Clone {
+        let (from, to) = (self.fit(x - radius, extent, n), self.fit(x + radius, extent, n));
+        let whole = 2.0 * radius >= extent;
+        let crosses = self.wrap && (whole || (x - radius).rem_euclid(extent) > (x + radius).rem_euclid(extent));
+        if !crosses {
+            (from..=to).chain(0..0)
+        } else if whole || from <= to {
+            (0..=n.max(1) - 1).chain(0..0) // all of them
+        } else {
+            (from..=n - 1).chain(0..to + 1)
+        }
+    }
+}
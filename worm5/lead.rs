@@ -7,7 +7,9 @@ use egui::{
 
 use crate::consts::{
     R_LEAD,
+    MS_LEAD,
     KF_LEAD,
+    INIT_X,
     INIT_Y,
     PURPLE1,
 };
@@ -29,9 +31,9 @@ impl Lead {
 
     pub fn default() -> Self {
         Self { 
-            position: vec2(90.0, INIT_Y),
+            position: vec2(INIT_X, INIT_Y),
             velocity: vec2(2.0, 0.0),
-            mass:     3.9,
+            mass:     MS_LEAD,
             kf:       KF_LEAD,
             ks:       0.03,
             radius:   R_LEAD,
@@ -39,9 +41,10 @@ impl Lead {
         }
     }
 
-    pub fn drive_me(&mut self, f: Vec2) {
-        self.velocity += (f - self.kf * self.velocity) / self.mass;
-        self.position += self.velocity;
+    // semi-implicit Euler: the new velocity moves the position
+    pub fn drive_me(&mut self, f: Vec2, dt: f32) {
+        self.velocity += dt * (f - self.kf * self.velocity) / self.mass;
+        self.position += dt * self.velocity;
     }
 
     pub fn paint(&self, painter: &Painter) {
@@ -0,0 +1,322 @@
+// A level is a layout of obstacles, read from a text file in the levels
+// directory:
+//
+//   name  = Pond
+//   rock  = x y radius
+//   wall  = x1 y1 x2 y2 width
+//   water = x1 y1 x2 y2
+//   spawn = x1 y1 x2 y2     foods only appear in spawn zones, if any
+//   start = x y             where the first worm starts
+//   vocab = Sea Animals     a vocabulary or pack by name
+//   difficulty = hard       easy, normal or hard
+//   letters = timid         the creature the letters are, see creature.rs
+//   predators = fast        and the one the predators are
+//   powers = shield hint    the power-ups that turn up, all if not given,
+//                           none for none
+//   letters_shown = 4       letters of each word on screen at once
+//   predator_count = 3      instead of the one of the difficulty
+//
+// Positions are fractions of the arena (0.0 to 1.0), so a level fits any
+// window; radius and width are in pixels.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use egui::{
+    Painter,
+    Vec2,
+    vec2,
+    pos2,
+    Color32,
+    Rect,
+    Stroke,
+};
+
+use crate::ai::{Skill, SKILLS};
+use crate::creature::Creature;
+use crate::power::{Power, POWERS};
+use crate::consts::{DIAMETER, INIT_X, INIT_Y, LETTERS_SHOWN, PREDATORS};
+
+pub const DIR:       &str = "levels";
+pub const MAX_SHOWN:     usize = 10;
+pub const MAX_PREDATORS: usize = 20;
+pub const NORMAL_ARENA: Vec2 = vec2(1280.0, 720.0); // the counts are meant for this, the server's
+pub const EXTENSION: &str = "level";
+
+const ROCK:  Color32 = Color32::from_rgb(120, 110, 100);
+const WALL:  Color32 = Color32::from_rgb(90, 80, 70);
+const WATER: Color32 = Color32::from_rgba_premultiplied(20, 60, 120, 120);
+
+
+#[derive(Clone, Copy)]
+pub enum Obstacle {
+    Rock  { center: Vec2, radius: f32 },
+    Wall  { from: Vec2, to: Vec2, width: f32 },
+    Water { min: Vec2, max: Vec2 }, // slows everything in it down
+}
+
+impl Obstacle {
+
+    // from level fractions to arena pixels
+    pub fn scaled(&self, size: Vec2) -> Self {
+        match *self {
+            Obstacle::Rock { center, radius } => Obstacle::Rock { center: center * size, radius },
+            Obstacle::Wall { from, to, width } => Obstacle::Wall { from: from * size, to: to * size, width },
+            Obstacle::Water { min, max } => Obstacle::Water { min: min * size, max: max * size },
+        }
+    }
+
+    pub fn translated(&self, by: Vec2) -> Self {
+        match *self {
+            Obstacle::Rock { center, radius } => Obstacle::Rock { center: center + by, radius },
+            Obstacle::Wall { from, to, width } => Obstacle::Wall { from: from + by, to: to + by, width },
+            Obstacle::Water { min, max } => Obstacle::Water { min: min + by, max: max + by },
+        }
+    }
+
+    // how far a circle has to move to get out of a rock or wall
+    pub fn push(&self, pos: Vec2, radius: f32) -> Option<Vec2> {
+        let (nearest, reach) = match *self {
+            Obstacle::Rock { center, radius: r } => (center, r + radius),
+            Obstacle::Wall { from, to, width } => (nearest_on(from, to, pos), 0.5 * width + radius),
+            Obstacle::Water { .. } => return None,
+        };
+        let seg = pos - nearest;
+        let dist = seg.length();
+        if dist >= reach {
+            return None;
+        }
+        let normal = if dist > 0.0 { seg / dist } else { vec2(0.0, -1.0) };
+        Some((reach - dist) * normal)
+    }
+
+    // the nearest point of a rock or wall, and how far its edge is from it
+    pub fn nearest_solid(&self, pos: Vec2) -> Option<(Vec2, f32)> {
+        match *self {
+            Obstacle::Rock { center, radius } => Some((center, radius)),
+            Obstacle::Wall { from, to, width } => Some((nearest_on(from, to, pos), 0.5 * width)),
+            Obstacle::Water { .. } => None,
+        }
+    }
+
+    pub fn is_water_at(&self, pos: Vec2) -> bool {
+        match *self {
+            Obstacle::Water { min, max } => Rect::from_min_max(min.to_pos2(), max.to_pos2()).contains(pos.to_pos2()),
+            _ => false,
+        }
+    }
+
+    // too close to spawn anything
+    pub fn covers(&self, pos: Vec2, margin: f32) -> bool {
+        match *self {
+            Obstacle::Water { min, max } => Rect::from_min_max(min.to_pos2(), max.to_pos2())
+                .expand(margin)
+                .contains(pos.to_pos2()),
+            _ => self.push(pos, margin).is_some(),
+        }
+    }
+
+    pub fn paint(&self, painter: &Painter) {
+        match *self {
+            Obstacle::Rock { center, radius } => {
+                painter.circle_filled(center.to_pos2(), radius, ROCK);
+            }
+            Obstacle::Wall { from, to, width } => {
+                painter.line_segment([from.to_pos2(), to.to_pos2()], Stroke::new(width, WALL));
+                painter.circle_filled(from.to_pos2(), 0.5 * width, WALL);
+                painter.circle_filled(to.to_pos2(), 0.5 * width, WALL);
+            }
+            Obstacle::Water { min, max } => {
+                painter.rect_filled(Rect::from_min_max(min.to_pos2(), max.to_pos2()), 8.0, WATER);
+            }
+        }
+    }
+}
+
+fn nearest_on(a: Vec2, b: Vec2, p: Vec2) -> Vec2 {
+    let ab = b - a;
+    let t = if ab.length_sq() > 0.0 { ((p - a).dot(ab) / ab.length_sq()).clamp(0.0, 1.0) } else { 0.0 };
+    a + t * ab
+}
+
+
+#[derive(Default, Clone)]
+pub struct Level {
+    pub name:      String,
+    pub obstacles: Vec<Obstacle>,
+    pub spawns:    Vec<Rect>,
+    pub start:     Option<Vec2>,
+    pub vocab:     Option<String>,
+    pub difficulty: Option<Skill>,
+    pub letters:   Option<String>, // creature names
+    pub predators: Option<String>,
+    pub powers:    Option<Vec<Power>>, // all of them when None
+    pub letters_shown:  Option<usize>, // on the normal arena size
+    pub predator_count: Option<usize>,
+}
+
+impl Level {
+    pub fn parse(text: &str) -> Self {
+        let mut level = Level::default();
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else { continue };
+            let v: Vec<f32> = value.split_whitespace().filter_map(|n| n.parse().ok()).collect();
+            match (key.trim(), v.len()) {
+                ("name", _) => level.name = value.trim().to_owned(),
+                ("rock", 3) => level.obstacles.push(Obstacle::Rock {
+                    center: vec2(v[0], v[1]),
+                    radius: v[2],
+                }),
+                ("wall", 5) => level.obstacles.push(Obstacle::Wall {
+                    from:  vec2(v[0], v[1]),
+                    to:    vec2(v[2], v[3]),
+                    width: v[4],
+                }),
+                ("water", 4) => level.obstacles.push(Obstacle::Water {
+                    min: vec2(v[0].min(v[2]), v[1].min(v[3])),
+                    max: vec2(v[0].max(v[2]), v[1].max(v[3])),
+                }),
+                ("spawn", 4) => level.spawns.push(Rect::from_two_pos(pos2(v[0], v[1]), pos2(v[2], v[3]))),
+                ("start", 2) => level.start = Some(vec2(v[0], v[1])),
+                ("vocab", _) => level.vocab = Some(value.trim().to_owned()),
+                ("letters", _) => level.letters = Some(value.trim().to_owned()),
+                ("predators", _) => level.predators = Some(value.trim().to_owned()),
+                ("letters_shown", 1) => level.letters_shown = Some((v[0] as usize).clamp(1, MAX_SHOWN)),
+                ("predator_count", 1) => level.predator_count = Some((v[0] as usize).min(MAX_PREDATORS)),
+                ("powers", _) => level.powers = Some(value.split_whitespace().filter_map(Power::from_name).collect()),
+                ("difficulty", _) => {
+                    let value = value.trim();
+                    level.difficulty = SKILLS.into_iter().find(|k| k.label().eq_ignore_ascii_case(value));
+                }
+                _ => {}
+            }
+        }
+        level
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Ok(Self::parse(&fs::read_to_string(path)?))
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut out = format!("name = {}\n", self.name);
+        if let Some(vocab) = &self.vocab {
+            out += &format!("vocab = {}\n", vocab);
+        }
+        if let Some(d) = self.difficulty {
+            out += &format!("difficulty = {}\n", d.label().to_lowercase());
+        }
+        if let Some(c) = &self.letters {
+            out += &format!("letters = {}\n", c);
+        }
+        if let Some(c) = &self.predators {
+            out += &format!("predators = {}\n", c);
+        }
+        if let Some(n) = self.letters_shown {
+            out += &format!("letters_shown = {}\n", n);
+        }
+        if let Some(n) = self.predator_count {
+            out += &format!("predator_count = {}\n", n);
+        }
+        if let Some(powers) = &self.powers {
+            let names: Vec<&str> = powers.iter().map(|p| p.name()).collect();
+            out += &format!("powers = {}\n", if names.is_empty() { "none".to_owned() } else { names.join(" ") });
+        }
+        if let Some(p) = self.start {
+            out += &format!("start = {:.3} {:.3}\n", p.x, p.y);
+        }
+        for o in &self.obstacles {
+            out += &match *o {
+                Obstacle::Rock { center, radius } => format!("rock = {:.3} {:.3} {:.0}\n", center.x, center.y, radius),
+                Obstacle::Wall { from, to, width } => format!("wall = {:.3} {:.3} {:.3} {:.3} {:.0}\n", from.x, from.y, to.x, to.y, width),
+                Obstacle::Water { min, max } => format!("water = {:.3} {:.3} {:.3} {:.3}\n", min.x, min.y, max.x, max.y),
+            };
+        }
+        for r in &self.spawns {
+            out += &format!("spawn = {:.3} {:.3} {:.3} {:.3}\n", r.min.x, r.min.y, r.max.x, r.max.y);
+        }
+        out
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_text())
+    }
+
+    // the obstacles in arena pixels
+    pub fn obstacles(&self, size: Vec2) -> Vec<Obstacle> {
+        self.obstacles.iter().map(|o| o.scaled(size)).collect()
+    }
+
+    pub fn spawns(&self, size: Vec2) -> Vec<Rect> {
+        self.spawns.iter().map(|r| Rect::from_min_max((r.min.to_vec2() * size).to_pos2(), (r.max.to_vec2() * size).to_pos2())).collect()
+    }
+
+    pub fn letter_kind(&self) -> Creature {
+        self.letters.as_deref().and_then(Creature::by_name).unwrap_or_else(Creature::bug)
+    }
+
+    pub fn predator_kind(&self) -> Creature {
+        self.predators.as_deref().and_then(Creature::by_name).unwrap_or_else(Creature::hunter)
+    }
+
+    // the level's own count, else the difficulty's, else the usual
+    pub fn n_predators(&self) -> usize {
+        self.predator_count
+            .or(self.difficulty.map(|d| d.predators()))
+            .unwrap_or(PREDATORS)
+    }
+
+    pub fn n_shown(&self) -> usize {
+        self.letters_shown.unwrap_or(LETTERS_SHOWN)
+    }
+
+    // the most foods a round can have: the letters shown of two words
+    // and the predators
+    pub fn most_foods(&self) -> usize {
+        2 * self.n_shown() + self.n_predators()
+    }
+
+    // about how many foods the spawn zones (the arena without any) hold on
+    // an arena this size, a food apart and clear of the obstacles
+    pub fn food_room(&self, size: Vec2) -> usize {
+        let arena = Rect::from_min_size(pos2(0.0, 0.0), size);
+        let obstacles = self.obstacles(size);
+        let mut zones = self.spawns(size);
+        if zones.is_empty() {
+            zones.push(arena);
+        }
+        // foods keep DIAMETER from the edge of a zone, like spawn_point
+        let zones: Vec<Rect> = zones.iter().map(|z| z.intersect(arena).shrink(DIAMETER)).collect();
+        let step = 2.0 * DIAMETER;
+        let mut room = 0;
+        for (k, z) in zones.iter().enumerate() {
+            if z.width() < 0.0 || z.height() < 0.0 {
+                continue;
+            }
+            let (nx, ny) = ((z.width() / step) as usize + 1, (z.height() / step) as usize + 1);
+            for i in 0..nx * ny {
+                let p = z.min + vec2((i % nx) as f32, (i / nx) as f32) * step;
+                // a place in an earlier zone is already counted
+                if zones[..k].iter().any(|o| o.contains(p)) || obstacles.iter().any(|o| o.covers(p.to_vec2(), DIAMETER)) {
+                    continue;
+                }
+                room += 1;
+            }
+        }
+        room
+    }
+
+    pub fn power_kinds(&self) -> Vec<Power> {
+        self.powers.clone().unwrap_or(POWERS.to_vec())
+    }
+
+    // where the first worm starts, in arena pixels
+    pub fn start(&self, size: Vec2) -> Vec2 {
+        self.start.map(|p| p * size).unwrap_or(vec2(INIT_X, INIT_Y))
+    }
+}
@@ -10,6 +10,23 @@ mod unit;
 mod food;
 mod game;
 mod worm;
+mod audio;
+mod pack;
+mod controls;
+mod player;
+mod spelling;
+mod world;
+mod ai;
+mod net;
+mod level;
+mod editor;
+mod camera;
+mod body;
+mod tuning;
+mod debug;
+mod creature;
+mod power;
+mod grid;
 
 use egui::ViewportBuilder;
 use game::Game;
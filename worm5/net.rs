@@ -0,0 +1,318 @@
+// The line protocol between caterpillar-server and the game. One message
+// per line, fields separated by spaces.
+//
+// client -> server
+//   INPUT F|L|R|B|-         the movement key held, - for none
+//   POINT x y               steer toward a point (mouse / touch)
+//   RAMP f ... f n          the key ramp of the player, see ramp_fields
+//
+// server -> client
+//   WELCOME i w h           you are player i, in an arena w by h
+//   WORD word               a new round, go for this word
+//   LEVEL|rock x y r|...    the obstacles, see level_line
+//   STATE w h|P ...|F ...   the arena, see state_line
+//   EVENT CAUGHT c | EVENT STOLEN | EVENT DROPPED | EVENT POWER name
+//   OVER message            the round is over
+//
+// The game and caterpillar-server each use their own half.
+#![allow(dead_code)]
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+use egui::{
+    Vec2,
+    vec2,
+};
+
+use crate::controls::{Action, Ramp};
+use crate::world::World;
+use crate::player::Player;
+use crate::food::Food;
+use crate::unit::Unit;
+use crate::consts::PALETTES;
+use crate::level::Obstacle;
+use crate::power::Power;
+
+pub const DEFAULT_ADDR: &str = "127.0.0.1:7878";
+
+
+pub enum ClientMsg {
+    Input(Option<Action>),
+    Point(Vec2),
+    Ramp(Ramp),
+}
+
+impl ClientMsg {
+    pub fn to_line(&self) -> String {
+        match self {
+            ClientMsg::Input(a) => format!("INPUT {}", action_code(*a)),
+            ClientMsg::Point(p) => format!("POINT {:.1} {:.1}", p.x, p.y),
+            ClientMsg::Ramp(r) => {
+                let mut r = *r;
+                let fields: Vec<String> = ramp_fields(&mut r).iter().map(|v| v.to_string()).collect();
+                format!("RAMP {} {}", fields.join(" "), r.release_frames)
+            }
+        }
+    }
+
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut f = line.split_whitespace();
+        match f.next()? {
+            "INPUT" => {
+                let action = match f.next()? {
+                    "F" => Some(Action::Forward),
+                    "L" => Some(Action::Left),
+                    "R" => Some(Action::Right),
+                    "B" => Some(Action::Brake),
+                    _ => None,
+                };
+                Some(ClientMsg::Input(action))
+            }
+            "POINT" => {
+                let x = f.next()?.parse().ok()?;
+                let y = f.next()?.parse().ok()?;
+                Some(ClientMsg::Point(vec2(x, y)))
+            }
+            "RAMP" => {
+                let mut r = Ramp::default();
+                for v in ramp_fields(&mut r) {
+                    *v = f.next()?.parse().ok()?;
+                }
+                r.release_frames = f.next()?.parse().ok()?;
+                Some(ClientMsg::Ramp(r))
+            }
+            _ => None,
+        }
+    }
+}
+
+// the numbers of a ramp as they go over the wire, release_frames after them
+fn ramp_fields(r: &mut Ramp) -> [&mut f32; 9] {
+    [
+        &mut r.forward_base, &mut r.forward_step, &mut r.max_forward,
+        &mut r.turn_base, &mut r.turn_step, &mut r.max_turn,
+        &mut r.spin_step, &mut r.max_spin, &mut r.curve,
+    ]
+}
+
+fn action_code(action: Option<Action>) -> &'static str {
+    match action {
+        Some(Action::Forward) => "F",
+        Some(Action::Left)    => "L",
+        Some(Action::Right)   => "R",
+        Some(Action::Brake)   => "B",
+        _ => "-",
+    }
+}
+
+
+// letters travel as themselves, - for none
+fn letter_code(c: Option<char>) -> char {
+    match c {
+        Some(' ') | None => '-',
+        Some(c) => c,
+    }
+}
+
+fn letter_from(s: &str) -> Option<char> {
+    match s.chars().next() {
+        Some('-') | None => None,
+        c => c,
+    }
+}
+
+
+// STATE w h|P hx hy nx ny score body x,y,l x,y,l ...|E power frames|...
+//   |F id x y angle tag owner|...|U x y power life|...|W eaten|...
+// E are the power-ups in effect of the player before them, U the ones
+// lying about, W the letters of each word eaten so far as 0 and 1
+pub fn state_line(world: &World) -> String {
+    let mut out = format!("STATE {:.0} {:.0}", world.size.x, world.size.y);
+    for p in &world.players {
+        let w = &p.worm;
+        out += &format!("|P {:.1} {:.1} {:.1} {:.1} {} {}",
+            w.head.position.x, w.head.position.y,
+            w.neck.position.x, w.neck.position.y,
+            p.score, w.body);
+        for u in &w.units {
+            out += &format!(" {:.1},{:.1},{}", u.position.x, u.position.y, letter_code(u.seq.map(|_| u.letter)));
+        }
+        for (power, frames) in &p.effects {
+            out += &format!("|E {} {}", power.name(), frames);
+        }
+    }
+    for fd in &world.foods {
+        out += &format!("|F {} {:.1} {:.1} {:.3} {} {}",
+            fd.id, fd.pos.x, fd.pos.y, fd.angle, letter_code(fd.tag), fd.owner);
+    }
+    for fd in &world.powers {
+        let Some(power) = fd.power else { continue };
+        out += &format!("|U {:.1} {:.1} {} {}", fd.pos.x, fd.pos.y, power.name(), fd.life);
+    }
+    for sp in &world.spellings {
+        let eaten: String = sp.eaten.iter().map(|e| if *e { '1' } else { '0' }).collect();
+        out += &format!("|W {}", eaten);
+    }
+    out
+}
+
+// rebuilds the worms and foods of a snapshot for painting
+pub fn apply_state(world: &mut World, line: &str) {
+    let mut parts = line.split('|');
+    let mut head = parts.next().unwrap_or("").split_whitespace().skip(1);
+    if let (Some(w), Some(h)) = (head.next(), head.next()) {
+        world.size = vec2(w.parse().unwrap_or(0.0), h.parse().unwrap_or(0.0));
+    }
+
+    let mut n_players = 0;
+    let mut n_foods = 0;
+    let mut n_words = 0;
+    world.powers.clear();
+    for part in parts {
+        let f: Vec<&str> = part.split_whitespace().collect();
+        match f.first() {
+            Some(&"P") if f.len() >= 7 => {
+                if world.players.len() <= n_players {
+                    world.players.push(Player::new(0));
+                }
+                let num = |i: usize| f[i].parse::<f32>().unwrap_or(0.0);
+                let p = &mut world.players[n_players];
+                let palette = PALETTES[n_players % PALETTES.len()];
+                p.score = f[5].parse().unwrap_or(0);
+                p.worm.palette = palette;
+                p.worm.body = f[6].parse().unwrap_or(0);
+                p.worm.head.position = vec2(num(1), num(2));
+                p.worm.neck.position = vec2(num(3), num(4));
+                p.worm.head.color = p.worm.color();
+                p.worm.neck.color = p.worm.color();
+                p.effects.clear();
+                p.worm.units.resize_with(f.len() - 7, Unit::default);
+                for (i, u) in f[7..].iter().enumerate() {
+                    let v: Vec<&str> = u.split(',').collect();
+                    if v.len() != 3 {
+                        continue;
+                    }
+                    let unit = &mut p.worm.units[i];
+                    unit.position = vec2(v[0].parse().unwrap_or(0.0), v[1].parse().unwrap_or(0.0));
+                    unit.letter = letter_from(v[2]).unwrap_or(' ');
+                    unit.color = match i {
+                        0 | 1 => palette[i + 1],
+                        _ => Unit::default().color,
+                    };
+                }
+                n_players += 1;
+            }
+            Some(&"F") if f.len() == 7 => {
+                if world.foods.len() <= n_foods {
+                    world.foods.push(Food::default());
+                }
+                let fd = &mut world.foods[n_foods];
+                fd.id    = f[1].parse().unwrap_or(0);
+                fd.pos   = vec2(f[2].parse().unwrap_or(0.0), f[3].parse().unwrap_or(0.0));
+                fd.angle = f[4].parse().unwrap_or(0.0);
+                fd.tag   = letter_from(f[5]);
+                fd.owner = f[6].parse().unwrap_or(0);
+                n_foods += 1;
+            }
+            Some(&"E") if f.len() == 3 && n_players > 0 => {
+                if let (Some(power), Ok(frames)) = (Power::from_name(f[1]), f[2].parse()) {
+                    world.players[n_players - 1].effects.push((power, frames));
+                }
+            }
+            Some(&"U") if f.len() == 5 => {
+                let mut fd = Food::default().set_pos(vec2(f[1].parse().unwrap_or(0.0), f[2].parse().unwrap_or(0.0)));
+                fd.power = Power::from_name(f[3]);
+                fd.life  = f[4].parse().unwrap_or(0);
+                world.powers.push(fd);
+            }
+            Some(&"W") if f.len() == 2 => {
+                if let Some(sp) = world.spellings.get_mut(n_words) {
+                    if sp.eaten.len() == f[1].len() {
+                        sp.eaten = f[1].chars().map(|c| c == '1').collect();
+                    }
+                }
+                n_words += 1;
+            }
+            _ => {}
+        }
+    }
+    world.players.truncate(n_players);
+    world.foods.truncate(n_foods);
+}
+
+
+// LEVEL|rock x y r|wall x1 y1 x2 y2 w|water x1 y1 x2 y2, in arena pixels
+pub fn level_line(obstacles: &[Obstacle]) -> String {
+    let mut out = "LEVEL".to_owned();
+    for o in obstacles {
+        out += &match *o {
+            Obstacle::Rock { center, radius } => format!("|rock {:.1} {:.1} {:.1}", center.x, center.y, radius),
+            Obstacle::Wall { from, to, width } => format!("|wall {:.1} {:.1} {:.1} {:.1} {:.1}", from.x, from.y, to.x, to.y, width),
+            Obstacle::Water { min, max } => format!("|water {:.1} {:.1} {:.1} {:.1}", min.x, min.y, max.x, max.y),
+        };
+    }
+    out
+}
+
+pub fn parse_level(line: &str) -> Vec<Obstacle> {
+    let mut out = Vec::new();
+    for part in line.split('|').skip(1) {
+        let mut f = part.split_whitespace();
+        let kind = f.next();
+        let v: Vec<f32> = f.filter_map(|n| n.parse().ok()).collect();
+        match (kind, v.len()) {
+            (Some("rock"), 3)  => out.push(Obstacle::Rock { center: vec2(v[0], v[1]), radius: v[2] }),
+            (Some("wall"), 5)  => out.push(Obstacle::Wall { from: vec2(v[0], v[1]), to: vec2(v[2], v[3]), width: v[4] }),
+            (Some("water"), 4) => out.push(Obstacle::Water { min: vec2(v[0], v[1]), max: vec2(v[2], v[3]) }),
+            _ => {}
+        }
+    }
+    out
+}
+
+
+// The game's end of the connection. A thread reads the lines so the
+// frame never waits for the network.
+pub struct Connection {
+    stream: TcpStream,
+    lines:  Receiver<String>,
+}
+
+impl Connection {
+    pub fn connect(addr: &str) -> io::Result<Self> {
+        let stream = TcpStream::connect(addr)?;
+        stream.set_nodelay(true)?;
+        let reader = BufReader::new(stream.try_clone()?);
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move|| {
+            for line in reader.lines() {
+                let Ok(line) = line else { break };
+                if tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Self { stream, lines: rx })
+    }
+
+    pub fn send(&mut self, msg: ClientMsg) -> io::Result<()> {
+        writeln!(self.stream, "{}", msg.to_line())
+    }
+
+    // the lines received so far, an error once the server is gone
+    pub fn poll(&mut self) -> io::Result<Vec<String>> {
+        let mut out = Vec::new();
+        loop {
+            match self.lines.try_recv() {
+                Ok(line) => out.push(line),
+                Err(TryRecvError::Empty) => return Ok(out),
+                Err(TryRecvError::Disconnected) => {
+                    return Err(io::Error::new(io::ErrorKind::ConnectionAborted, "server closed"));
+                }
+            }
+        }
+    }
+}
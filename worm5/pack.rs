@@ -0,0 +1,252 @@
+// A vocabulary pack bundles the word list, the emoji and the audio
+// of one vocabulary into a single file:
+//
+//   b"CATPACK2"  magic
+//   u32          number of entries
+//   per entry:   u16 name length, name, u64 data length, data
+//
+// The data of every entry is deflated; packs starting with b"CATPACK1"
+// hold it as it is and are still read. All integers are little-endian. The entry "pack.txt" holds the metadata:
+//
+//   name  = Dinosaurs
+//   title = dinosaurs         (audio played when the pack is selected)
+//   word  = stegosaurus 🦕    (the emoji is optional)
+//
+// Audio entries are stored as "sounds/<name>.<ext>", ext being ogg, flac or wav.
+//
+// The game reads packs, the caterpillar-pack tool writes them.
+#![allow(dead_code)]
+
+use std::fs;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::io::{self, Read, Write, Error, ErrorKind};
+use std::path::{Path, PathBuf, Component};
+
+use flate2::Compression;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+
+pub const MAGIC:     &[u8; 8] = b"CATPACK2";
+pub const MAGIC_RAW: &[u8; 8] = b"CATPACK1"; // not deflated
+pub const MAX_ENTRY: u64      = 64 << 20;    // bytes an entry may inflate to
+pub const META:      &str     = "pack.txt";
+pub const EXTENSION: &str     = "catpack";
+
+
+#[derive(Default)]
+pub struct Meta {
+    pub name:  String,
+    pub title: Option<String>,
+    pub words: Vec<(String, Option<String>)>,
+}
+
+impl Meta {
+    pub fn parse(text: &str) -> Self {
+        let mut meta = Meta::default();
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else { continue };
+            let value = value.trim();
+            match key.trim() {
+                "name"  => meta.name  = value.to_owned(),
+                "title" => meta.title = Some(value.to_owned()),
+                "word"  => {
+                    let mut parts = value.split_whitespace();
+                    if let Some(word) = parts.next() {
+                        meta.words.push((word.to_owned(), parts.next().map(|e| e.to_owned())));
+                    }
+                }
+                _ => {}
+            }
+        }
+        meta
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut out = format!("name = {}\n", self.name);
+        if let Some(title) = &self.title {
+            out += &format!("title = {}\n", title);
+        }
+        for (word, emoji) in &self.words {
+            match emoji {
+                Some(e) => out += &format!("word = {} {}\n", word, e),
+                None    => out += &format!("word = {}\n", word),
+            }
+        }
+        out
+    }
+}
+
+
+pub struct Pack {
+    pub meta:  Meta,
+    pub files: Vec<(String, Vec<u8>)>,
+}
+
+impl Pack {
+    pub fn new(meta: Meta) -> Self {
+        Self { meta, files: Vec::new() }
+    }
+
+    pub fn add_file(&mut self, name: String, data: Vec<u8>) {
+        self.files.push((name, data));
+    }
+
+    pub fn read(path: &Path) -> io::Result<Self> {
+        Self::read_some(path, true)
+    }
+
+    // the metadata alone, the files are skipped
+    pub fn read_meta(path: &Path) -> io::Result<Meta> {
+        Ok(Self::read_some(path, false)?.meta)
+    }
+
+    fn read_some(path: &Path, with_files: bool) -> io::Result<Self> {
+        let mut r = io::BufReader::new(fs::File::open(path)?);
+        let mut magic = [0u8; 8];
+        r.read_exact(&mut magic)?;
+        let deflated = match &magic {
+            MAGIC     => true,
+            MAGIC_RAW => false,
+            _ => return Err(Error::new(ErrorKind::InvalidData, "not a vocabulary pack")),
+        };
+        let read_entry = |r: &mut io::BufReader<fs::File>, len: u64| {
+            let data = read_data(r, len)?;
+            if deflated { inflate(&data) } else { Ok(data) }
+        };
+        let mut meta = None;
+        let mut files = Vec::new();
+        let count = read_u32(&mut r)?;
+        for _ in 0..count {
+            let len = read_u16(&mut r)? as usize;
+            let mut name = vec![0u8; len];
+            r.read_exact(&mut name)?;
+            let name = String::from_utf8(name)
+                .map_err(|_| Error::new(ErrorKind::InvalidData, "bad entry name"))?;
+            let len = read_u64(&mut r)?;
+            if name == META {
+                let data = read_entry(&mut r, len)?;
+                meta = Some(Meta::parse(&String::from_utf8_lossy(&data)));
+                if !with_files {
+                    break;
+                }
+            } else if with_files {
+                files.push((name, read_entry(&mut r, len)?));
+            } else {
+                let skipped = io::copy(&mut r.by_ref().take(len), &mut io::sink())?;
+                if skipped != len {
+                    return Err(truncated());
+                }
+            }
+        }
+        let meta = meta.ok_or_else(|| Error::new(ErrorKind::InvalidData, "missing pack.txt"))?;
+        Ok(Self { meta, files })
+    }
+
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let mut w = io::BufWriter::new(fs::File::create(path)?);
+        w.write_all(MAGIC)?;
+        w.write_all(&(self.files.len() as u32 + 1).to_le_bytes())?;
+        write_entry(&mut w, META, self.meta.to_text().as_bytes())?;
+        for (name, data) in &self.files {
+            write_entry(&mut w, name, data)?;
+        }
+        w.flush()
+    }
+
+    // unpacks the files below dir, returns the directory holding the sounds;
+    // entries that would land anywhere else are left out
+    pub fn extract(&self, dir: &Path) -> io::Result<PathBuf> {
+        for (name, data) in &self.files {
+            let Some(name) = relative(name) else { continue };
+            let path = dir.join(name);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, data)?;
+        }
+        Ok(dir.join("sounds"))
+    }
+
+    // one plain directory name to unpack the pack read from path into:
+    // the name for reading, and a hash of the path so that two packs
+    // with the same name do not share it
+    pub fn dir_name(&self, path: &Path) -> String {
+        let name: String = self.meta.name.chars()
+            .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' || c == ' ' { c } else { '_' })
+            .collect();
+        let name = name.trim();
+        let name = if name.is_empty() { "pack" } else { name };
+        let mut hasher = DefaultHasher::new();
+        path.canonicalize().unwrap_or_else(|_| path.to_path_buf()).hash(&mut hasher);
+        format!("{}-{:016x}", name, hasher.finish())
+    }
+}
+
+// a path that stays below the directory it is joined to: no root, no
+// drive, no "..", nothing but plain names
+fn relative(name: &str) -> Option<&Path> {
+    let path = Path::new(name);
+    let plain = path.components().all(|c| matches!(c, Component::Normal(_)));
+    (plain && path.components().next().is_some()).then_some(path)
+}
+
+
+fn write_entry(w: &mut impl Write, name: &str, data: &[u8]) -> io::Result<()> {
+    let len = u16::try_from(name.len())
+        .map_err(|_| Error::new(ErrorKind::InvalidInput, "entry name longer than 65535 bytes"))?;
+    let mut deflate = DeflateEncoder::new(Vec::new(), Compression::best());
+    deflate.write_all(data)?;
+    let data = deflate.finish()?;
+    w.write_all(&len.to_le_bytes())?;
+    w.write_all(name.as_bytes())?;
+    w.write_all(&(data.len() as u64).to_le_bytes())?;
+    w.write_all(&data)
+}
+
+// an entry as it was packed, refusing to grow past MAX_ENTRY
+fn inflate(data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut out = Vec::new();
+    DeflateDecoder::new(data).take(MAX_ENTRY + 1).read_to_end(&mut out)?;
+    if out.len() as u64 > MAX_ENTRY {
+        return Err(Error::new(ErrorKind::InvalidData, "pack entry too big"));
+    }
+    Ok(out)
+}
+
+// len bytes, as far as the file has them: a length that is too big is an
+// error, not a huge allocation
+fn read_data(r: &mut impl Read, len: u64) -> io::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    r.by_ref().take(len).read_to_end(&mut data)?;
+    if data.len() as u64 != len {
+        return Err(truncated());
+    }
+    Ok(data)
+}
+
+fn truncated() -> Error {
+    Error::new(ErrorKind::UnexpectedEof, "truncated pack")
+}
+
+fn read_u16(r: &mut impl Read) -> io::Result<u16> {
+    let mut b = [0u8; 2];
+    r.read_exact(&mut b)?;
+    Ok(u16::from_le_bytes(b))
+}
+
+fn read_u32(r: &mut impl Read) -> io::Result<u32> {
+    let mut b = [0u8; 4];
+    r.read_exact(&mut b)?;
+    Ok(u32::from_le_bytes(b))
+}
+
+fn read_u64(r: &mut impl Read) -> io::Result<u64> {
+    let mut b = [0u8; 8];
+    r.read_exact(&mut b)?;
+    Ok(u64::from_le_bytes(b))
+}
@@ -0,0 +1,195 @@
+use std::collections::HashSet;
+
+use egui::{
+    Key,
+    Vec2,
+    vec2,
+};
+
+use crate::worm::Worm;
+use crate::ai::Ai;
+use crate::controls::{Controls, Action, Ramp};
+use crate::power::Power;
+
+use crate::consts::{
+    R_LEAD,
+    POINTER_GAIN,
+    POINTER_SPEED,
+    POINTER_FORCE,
+    ANALOG_GRIP,
+};
+
+
+pub struct Player {
+    pub worm:      Worm,
+    pub spelling:  usize,         // index of the word this player spells
+    pub score:     u32,           // letters eaten or stolen
+    pub target:    Option<usize>, // id of the food to steer toward
+    pub cooldown:  u32,           // frames until the next steal
+    pub ai:        Option<Ai>,    // None for people
+    pub heading:   Option<f32>,   // of analog steering, from the body when None
+    pub effects:   Vec<(Power, u32)>, // power-ups in effect, with frames left
+    forward_f:     f32,
+    left_f:        f32,
+    right_f:       f32,
+    spin_f:        f32, // share of max_spin, before the curve
+    held:          Option<Action>,
+    release_count: u32,
+}
+
+impl Player {
+    pub fn new(spelling: usize) -> Self {
+        Self {
+            worm:          Worm::default(),
+            spelling,
+            score:         0,
+            target:        None,
+            cooldown:      0,
+            ai:            None,
+            heading:       None,
+            effects:       Vec::new(),
+            forward_f:     0.0,
+            left_f:        0.0,
+            right_f:       0.0,
+            spin_f:        0.0,
+            held:          None,
+            release_count: 0,
+        }
+    }
+
+    pub fn has(&self, power: Power) -> bool {
+        self.effects.iter().any(|e| e.0 == power)
+    }
+
+    // a power-up eaten again starts over
+    pub fn give(&mut self, power: Power) {
+        self.effects.retain(|e| e.0 != power);
+        self.effects.push((power, power.frames()));
+    }
+
+    pub fn tick_effects(&mut self) {
+        for e in &mut self.effects {
+            e.1 = e.1.saturating_sub(1);
+        }
+        self.effects.retain(|e| e.1 > 0);
+    }
+
+    pub fn calc_input_force(&mut self, controls: &Controls, keys_down: &HashSet<Key>) -> Vec2 {
+        let action = controls.movement(keys_down);
+        self.action_force(action, &controls.ramp)
+    }
+
+    // the force of a held movement key, people and computer alike
+    pub fn action_force(&mut self, mut action: Option<Action>, ramp: &Ramp) -> Vec2 {
+        if action.is_some() {
+            self.held = action;
+            self.release_count = 0;
+        } else if self.held.is_some() && self.release_count < ramp.release_frames {
+            self.release_count += 1;  // debounce: keep the key held a little longer
+            action = self.held;
+        } else {
+            self.held = None;
+        }
+
+        let ang = (self.worm.head.position - self.worm.neck.position).angle();
+
+        match action {
+            Some(Action::Forward) => {
+                self.forward_f += ramp.forward_step;
+                self.forward_f = self.forward_f.min(ramp.max_forward);
+                self.left_f    = 0.0;
+                self.right_f   = 0.0;
+                vec2(
+                    (self.forward_f+ramp.forward_base) * ang.cos(),
+                    (self.forward_f+ramp.forward_base) * ang.sin()
+                )
+            }
+            Some(Action::Right) => {
+                self.worm.head.velocity *= 0.99;
+                self.left_f    = 0.0;
+                self.forward_f = 0.0;
+                self.right_f  += ramp.turn_step;
+                self.right_f   = self.right_f.min(ramp.max_turn);
+                vec2(
+                    -(self.right_f+ramp.turn_base) * ang.sin(),
+                     (self.right_f+ramp.turn_base) * ang.cos()
+                )
+            }
+            Some(Action::Left) => {
+                self.worm.head.velocity *= 0.99;
+                self.right_f   = 0.0;
+                self.forward_f = 0.0;
+                self.left_f   += ramp.turn_step;
+                self.left_f    = self.left_f.min(ramp.max_turn);
+                vec2(
+                     (self.left_f+ramp.turn_base) * ang.sin(),
+                    -(self.left_f+ramp.turn_base) * ang.cos()
+                )
+            }
+            Some(Action::Brake) => {
+                self.worm.head.velocity *= 0.95;
+                self.forward_f = 0.0;
+                self.left_f    = 0.0;
+                self.right_f   = 0.0;
+                vec2(0.0, 0.0)
+            }
+            _ => { // no keys or other keys
+                self.left_f    = 0.0;
+                self.right_f   = 0.0;
+                self.forward_f = 0.0;
+                vec2(0.0, 0.0)
+            }
+        }
+    }
+
+    // Analog steering: every movement key counts at once. Turning spins the
+    // heading, thrust pushes along it and the grip takes out sideways drift,
+    // so the worm curves instead of being pushed sideways.
+    pub fn calc_analog_force(&mut self, controls: &Controls, keys_down: &HashSet<Key>) -> Vec2 {
+        let ramp = &controls.ramp;
+        let down = |a: Action| controls.down(keys_down, a);
+        let body = (self.worm.head.position - self.worm.neck.position).angle();
+        let mut heading = self.heading.unwrap_or(body);
+
+        let turn = down(Action::Right) as i32 - down(Action::Left) as i32;
+        if turn != 0 {
+            self.spin_f = (self.spin_f + ramp.spin_step).min(1.0);
+        } else {
+            self.spin_f = 0.0;
+        }
+        heading += turn as f32 * ramp.max_spin * self.spin_f.powf(ramp.curve);
+        self.heading = Some(heading);
+
+        let thrust = if down(Action::Forward) {
+            self.forward_f = (self.forward_f + ramp.forward_step).min(ramp.max_forward);
+            ramp.forward_base + ramp.max_forward * (self.forward_f / ramp.max_forward).powf(ramp.curve)
+        } else {
+            self.forward_f = 0.0;
+            0.0
+        };
+        if down(Action::Brake) {
+            self.worm.head.velocity *= 0.95;
+        }
+
+        let dir = Vec2::angled(heading);
+        let v = self.worm.head.velocity;
+        let side = v - v.dot(dir) * dir;
+        thrust * dir - ANALOG_GRIP * side
+    }
+
+    pub fn steer_toward(&self, target: Vec2) -> Vec2 {
+        let seg = target - self.worm.head.position;
+        let dist = seg.length();
+        let desired = if dist < R_LEAD {
+            vec2(0.0, 0.0) // arrived, slow down
+        } else {
+            seg / dist * (dist * POINTER_GAIN).min(POINTER_SPEED)
+        };
+        let f = desired - self.worm.head.velocity;
+        if f.length() > POINTER_FORCE {
+            f.normalized() * POINTER_FORCE
+        } else {
+            f
+        }
+    }
+}
@@ -0,0 +1,89 @@
+// Power-ups: special foods that drift about and give the worm eating them
+// something for a while.
+//
+//   speed   the head pulls harder
+//   shield  predators bounce off the worm
+//   magnet  the next letter of the word is drawn to the head
+//   slow    all the foods move in slow motion
+//   hint    the next letter is marked
+
+use egui::Color32;
+
+pub const POWER_EVERY:  u32   = 600;  // frames between two power-ups appearing
+pub const POWER_LIFE:   u32   = 900;  // frames one stays before it fades away
+pub const MAX_POWERS:   usize = 2;    // in the arena at once
+pub const SPEED_BOOST:  f32   = 1.6;  // times the steering force
+pub const SLOW_MOTION:  f32   = 0.4;  // times the food speed
+pub const MAGNET_RADIUS: f32  = 250.0;
+pub const MAGNET_PULL:  f32   = 1.2;  // pixels per frame
+
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Power {
+    Speed,
+    Shield,
+    Magnet,
+    Slow,
+    Hint,
+}
+
+pub const POWERS: [Power; 5] = [Power::Speed, Power::Shield, Power::Magnet, Power::Slow, Power::Hint];
+
+impl Power {
+    // as written in level files and over the network
+    pub fn name(&self) -> &'static str {
+        match self {
+            Power::Speed  => "speed",
+            Power::Shield => "shield",
+            Power::Magnet => "magnet",
+            Power::Slow   => "slow",
+            Power::Hint   => "hint",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        POWERS.into_iter().find(|p| p.name().eq_ignore_ascii_case(name))
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Power::Speed  => "Speed",
+            Power::Shield => "Shield",
+            Power::Magnet => "Magnet",
+            Power::Slow   => "Slow motion",
+            Power::Hint   => "Hint",
+        }
+    }
+
+    // painted on the food
+    pub fn symbol(&self) -> char {
+        match self {
+            Power::Speed  => '>',
+            Power::Shield => 'O',
+            Power::Magnet => 'U',
+            Power::Slow   => 'z',
+            Power::Hint   => '?',
+        }
+    }
+
+    pub fn color(&self) -> Color32 {
+        match self {
+            Power::Speed  => Color32::from_rgb(230, 126, 34),
+            Power::Shield => Color32::from_rgb(52, 152, 219),
+            Power::Magnet => Color32::from_rgb(231, 76, 60),
+            Power::Slow   => Color32::from_rgb(155, 89, 182),
+            Power::Hint   => Color32::from_rgb(241, 196, 15),
+        }
+    }
+
+    // how long the effect lasts
+    pub fn frames(&self) -> u32 {
+        match self {
+            Power::Speed  => 300,
+            Power::Shield => 480,
+            Power::Magnet => 360,
+            Power::Slow   => 360,
+            Power::Hint   => 600,
+        }
+    }
+}
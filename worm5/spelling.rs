@@ -0,0 +1,38 @@
+// A word being spelled. Letters are eaten in order, but a predator or
+// another worm can knock any of them off again, so every letter keeps
+// its own flag instead of a count.
+
+pub struct Spelling {
+    pub word:    String,
+    pub letters: Vec<char>,
+    pub eaten:   Vec<bool>,
+}
+
+impl Spelling {
+    pub fn new(word: &str) -> Self {
+        let letters: Vec<char> = word.chars().collect();
+        Self {
+            word:    word.to_owned(),
+            eaten:   vec![false; letters.len()],
+            letters,
+        }
+    }
+
+    // the position of the letter to eat next
+    pub fn next(&self) -> Option<usize> {
+        self.eaten.iter().position(|e| !e)
+    }
+
+    pub fn next_letter(&self) -> Option<char> {
+        self.next().map(|i| self.letters[i])
+    }
+
+    // the first n letters still to eat, these are the ones on screen
+    pub fn wanted(&self, n: usize) -> Vec<usize> {
+        (0..self.letters.len()).filter(|&i| !self.eaten[i]).take(n).collect()
+    }
+
+    pub fn done(&self) -> bool {
+        self.eaten.iter().all(|e| *e)
+    }
+}
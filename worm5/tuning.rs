@@ -0,0 +1,120 @@
+// The numbers behind the feel of the worm, changed live from the tuning
+// panel. Presets are kept by name in presets.cfg:
+//
+//   [Floaty]
+//   head_mass = 3.9
+//   springs.stiffness = 0.03
+//   max_forward = 0.17
+//
+// Keys that are missing keep their current value.
+
+use std::fs;
+
+use crate::body::Param;
+use crate::worm::Worm;
+use crate::consts::{
+    MS_LEAD,
+    KF_LEAD,
+    L0_LEAD,
+    MS_NECK,
+    KF_NECK,
+    KS_NECK,
+    L0_UNIT,
+};
+
+pub const PRESETS_FILE: &str = "presets.cfg";
+
+
+#[derive(Clone, Copy)]
+pub struct Tuning {
+    pub head_mass: f32,
+    pub head_kf:   f32,
+    pub neck_mass: f32,
+    pub neck_kf:   f32,
+    pub neck_ks:   f32,
+    pub l0_lead:   f32, // head to neck
+    pub l0_unit:   f32, // between units
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Self {
+            head_mass: MS_LEAD,
+            head_kf:   KF_LEAD,
+            neck_mass: MS_NECK,
+            neck_kf:   KF_NECK,
+            neck_ks:   KS_NECK,
+            l0_lead:   L0_LEAD,
+            l0_unit:   L0_UNIT,
+        }
+    }
+}
+
+impl Tuning {
+    pub fn params(&mut self) -> Vec<Param<'_>> {
+        vec![
+            Param { name: "head mass",     value: &mut self.head_mass, range: 0.5..=10.0 },
+            Param { name: "head friction", value: &mut self.head_kf,   range: 0.0..=0.2 },
+            Param { name: "neck mass",     value: &mut self.neck_mass, range: 0.02..=1.0 },
+            Param { name: "neck friction", value: &mut self.neck_kf,   range: 0.01..=0.5 },
+            Param { name: "neck spring",   value: &mut self.neck_ks,   range: 0.005..=0.3 },
+            Param { name: "neck length",   value: &mut self.l0_lead,   range: 2.0..=30.0 },
+            Param { name: "unit length",   value: &mut self.l0_unit,   range: 8.0..=40.0 },
+        ]
+    }
+
+    pub fn apply(&self, worm: &mut Worm) {
+        worm.head.mass = self.head_mass;
+        worm.head.kf   = self.head_kf;
+        worm.neck.set_params(self.neck_mass, self.neck_kf, self.neck_ks);
+        worm.l0_lead = self.l0_lead;
+        for u in &mut worm.units {
+            u.l0 = self.l0_unit;
+        }
+    }
+}
+
+
+// "head mass" -> head_mass, "Springs", "stiffness" -> springs.stiffness
+pub fn key(group: Option<&str>, name: &str) -> String {
+    let name = name.replace(' ', "_");
+    match group {
+        Some(g) => format!("{}.{}", g.to_lowercase(), name),
+        None => name,
+    }
+}
+
+// the presets in the file, each a name and its keys and values
+pub fn load_presets() -> Vec<(String, Vec<(String, f32)>)> {
+    let mut presets: Vec<(String, Vec<(String, f32)>)> = Vec::new();
+    let Ok(text) = fs::read_to_string(PRESETS_FILE) else { return presets };
+    for line in text.lines() {
+        let line = line.trim();
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            presets.push((name.trim().to_owned(), Vec::new()));
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else { continue };
+        let (Some(preset), Ok(value)) = (presets.last_mut(), value.trim().parse()) else { continue };
+        preset.1.push((key.trim().to_owned(), value));
+    }
+    presets
+}
+
+// adds a preset to the file, or replaces the one with the same name
+pub fn save_preset(name: &str, values: Vec<(String, f32)>) {
+    let mut presets = load_presets();
+    presets.retain(|p| p.0 != name);
+    presets.push((name.to_owned(), values));
+    let mut out = String::new();
+    for (name, values) in &presets {
+        out += &format!("[{}]\n", name);
+        for (key, value) in values {
+            out += &format!("{} = {}\n", key, value);
+        }
+        out += "\n";
+    }
+    if let Err(e) = fs::write(PRESETS_FILE, out) {
+        eprintln!("cannot save {}: {}", PRESETS_FILE, e);
+    }
+}
@@ -0,0 +1,682 @@
+use rand::Rng;
+use std::f32::consts::PI;
+
+use egui::{
+    Vec2,
+    vec2,
+    Rect,
+};
+
+use crate::consts::{
+    PlayMode,
+    HEAD_SIZE,
+    FOOD_LEN,
+    DIAMETER,
+    INIT_X,
+    INIT_Y,
+    PLAYER_GAP,
+    R_LEAD,
+    R_UNIT,
+    LETTERS_SHOWN,
+    PREDATORS,
+    STEAL_COOLDOWN,
+    SELF_SKIP,
+    HALF_SIZE,
+    FOOD_SPEED,
+    WATER_SLOW,
+    PALETTES,
+    wrap_delta,
+};
+
+use crate::food::Food;
+use crate::ai::Ai;
+use crate::player::Player;
+use crate::spelling::Spelling;
+use crate::level::Obstacle;
+use crate::body::{self, BodyModel};
+use crate::unit::Unit;
+use crate::creature::Creature;
+use crate::grid::Grid;
+use crate::power::{
+    Power,
+    POWERS,
+    POWER_EVERY,
+    POWER_LIFE,
+    MAX_POWERS,
+    SPEED_BOOST,
+    SLOW_MOTION,
+    MAGNET_RADIUS,
+    MAGNET_PULL,
+};
+
+const MIN_DIST: f32 = 2.0 * R_LEAD;
+const TOUCH:    f32 = R_UNIT + 0.5 * HEAD_SIZE;
+const SPAWN_TRIES: usize = 200; // places tried for a food before it may crowd in
+
+// how close things have to be for the foods to react
+pub const AVOID_RADIUS: f32 = 5.0 * HEAD_SIZE; // foods steer away from each other
+pub const FLEE_RADIUS:  f32 = 6.0 * HEAD_SIZE; // letters run from a head
+pub const CHASE_RADIUS: f32 = 5.0 * TOUCH;     // predators go for a worm
+
+
+// What happened during a step, for the sounds and the screens.
+pub enum Event {
+    Caught { letter: char },
+    Stolen,
+    Dropped, // bumped into itself and lost the last letter
+    WordDone { player: usize },
+    Lost { player: usize },
+    PowerUp { player: usize, power: Power },
+}
+
+
+pub struct World {
+    pub players:   Vec<Player>,
+    pub spellings: Vec<Spelling>,
+    pub foods:     Vec<Food>,
+    pub size:      Vec2,
+    pub events:    Vec<Event>,
+    pub obstacles: Vec<Obstacle>, // in arena pixels
+    pub spawns:    Vec<Rect>,     // where foods may appear, anywhere if empty
+    pub start:     Vec2,          // the head of the first worm
+    pub predators: usize,
+    pub letters_shown: usize, // of each word at once
+    pub self_penalty: bool, // bumping into yourself costs the last letter
+    pub wrap:      bool,      // leaving one edge comes back in at the other
+    pub constraints: bool,    // worms keep their links at rest length
+    pub frames:    f32,       // 1/60 s frames since the last step, the worms move that far
+    pub bodies:    Vec<Box<dyn BodyModel>>, // shared by the worms, tuned at runtime
+    pub body:      usize,     // the model new worms start with
+    pub letter_kind:   Creature, // how the letters move
+    pub predator_kind: Creature, // and the foods without one
+    pub powers:    Vec<Food>,     // power-ups in the arena
+    pub power_kinds: Vec<Power>,  // the ones that may turn up
+    pub power_timer: u32,         // frames since the last one
+    pub food_grid: Grid<usize>,   // food indices, rebuilt every step
+    pub unit_grid: Grid<(usize, usize)>, // player and unit indices
+}
+
+impl Default for World {
+    fn default() -> Self {
+        Self {
+            players:   Vec::new(),
+            spellings: Vec::new(),
+            foods:     Vec::new(),
+            size:      vec2(0.0, 0.0),
+            events:    Vec::new(),
+            obstacles: Vec::new(),
+            spawns:    Vec::new(),
+            start:     vec2(INIT_X, INIT_Y),
+            predators: PREDATORS,
+            letters_shown: LETTERS_SHOWN,
+            self_penalty: false,
+            wrap:      false,
+            constraints: false,
+            frames:    1.0,
+            bodies:    body::models(),
+            body:      0,
+            letter_kind:   Creature::bug(),
+            predator_kind: Creature::hunter(),
+            powers:    Vec::new(),
+            power_kinds: POWERS.to_vec(),
+            power_timer: 0,
+            food_grid: Grid::default(),
+            unit_grid: Grid::default(),
+        }
+    }
+}
+
+
+impl World {
+
+    pub fn start(&mut self, mode: PlayMode, words: Vec<String>, ai: Option<Ai>) {
+        self.players.clear();
+        for i in 0..mode.n_players() {
+            self.players.push(Player::new(i.min(words.len()-1)));
+        }
+        if let Some(last) = self.players.last_mut() {
+            last.ai = ai.filter(|_| mode == PlayMode::VsComputer);
+        }
+        self.restart(words);
+    }
+
+    // a new round with the same players
+    pub fn restart(&mut self, words: Vec<String>) {
+        self.spellings = words.iter().map(|w| Spelling::new(w)).collect();
+        for i in 0..self.players.len() {
+            self.place(i);
+        }
+        self.events.clear();
+        self.powers.clear();
+        self.power_timer = 0;
+        self.create_foods();
+    }
+
+    // a player joining a running game, returns its index;
+    // join and leave are for caterpillar-server
+    #[allow(dead_code)]
+    pub fn join(&mut self) -> usize {
+        let i = self.players.len();
+        self.players.push(Player::new(0));
+        self.place(i);
+        i
+    }
+
+    #[allow(dead_code)]
+    pub fn leave(&mut self, i: usize) {
+        self.players.remove(i);
+    }
+
+    fn place(&mut self, i: usize) {
+        let start = self.start_point(i);
+        let p = &mut self.players[i];
+        p.score = 0;
+        p.target = None;
+        p.heading = None;
+        p.cooldown = 0;
+        p.effects.clear();
+        p.worm.body = self.body;
+        p.worm.reset(start, PALETTES[i % PALETTES.len()]);
+    }
+
+    // the worms start one below the other, in as many rows as the arena has
+    // room for below the start, then in the next column to the right; once
+    // all those places are taken the next ones go in between
+    fn start_point(&self, i: usize) -> Vec2 {
+        let room = self.size - self.start;
+        let rows = ((room.y / PLAYER_GAP) as usize).max(1);
+        let cols = ((room.x / (2.0 * PLAYER_GAP)) as usize).max(1);
+        let places = rows * cols;
+        let (row, col) = (i % places % rows, i % places / rows);
+        let between = ((i / places) as f32 * 0.618).fract(); // somewhere new each time round
+        self.start + vec2((col as f32 + between) * 2.0 * PLAYER_GAP, (row as f32 + between) * PLAYER_GAP)
+    }
+
+    pub fn ai_force(&mut self, p: usize) -> Vec2 {
+        let Some(mut ai) = self.players[p].ai.take() else {
+            return vec2(0.0, 0.0);
+        };
+        let action = ai.think(self, p);
+        let ramp = ai.ramp();
+        self.players[p].ai = Some(ai);
+        self.players[p].action_force(action, &ramp)
+    }
+
+    pub fn step(&mut self, forces: &[Vec2]) {
+        for (p, f) in self.players.iter_mut().zip(forces) {
+            p.worm.constraints = self.constraints;
+            let body = &mut *self.bodies[p.worm.body];
+            let f = if p.has(Power::Speed) { SPEED_BOOST * *f } else { *f };
+            if self.wrap {
+                p.worm.unwrap(self.size);
+                p.worm.drive_me(f, body, self.frames);
+                p.worm.wrap(self.size);
+            } else {
+                p.worm.drive_me(f, body, self.frames);
+                p.worm.cross_border(self.size);
+            }
+            p.worm.hit_obstacles(&self.obstacles);
+            p.cooldown = p.cooldown.saturating_sub(1);
+            p.tick_effects();
+        }
+        let slow = if self.players.iter().any(|p| p.has(Power::Slow)) { SLOW_MOTION } else { 1.0 };
+        self.pull_letters();
+        self.index_foods();
+        self.index_units();
+
+        let turns: Vec<f32> = (0..self.foods.len()).map(|i| self.kind(i).turn(self, i)).collect();
+        for (fd, turn) in self.foods.iter_mut().zip(turns) {
+            fd.angle += turn;
+        }
+
+        for fd in &mut self.foods  {
+            let kind = if fd.tag.is_some() { &self.letter_kind } else { &self.predator_kind };
+            let wet = self.obstacles.iter().any(|o| o.is_water_at(fd.pos));
+            fd.speed = slow * kind.speed * if wet { WATER_SLOW * FOOD_SPEED } else { FOOD_SPEED };
+            fd.move_me(self.size, self.wrap);
+            for o in &self.obstacles {
+                if let Some(push) = o.push(fd.pos, HALF_SIZE) {
+                    bounce(fd, push);
+                }
+            }
+            fd.wag_tail(); // the letter is caught by its tail, painted or not
+        }
+        self.index_foods(); // where they are caught
+
+        for p in 0..self.players.len() {
+            if let Some(idx) = self.find_food(p) {
+                self.handling_caught(p, idx);
+            }
+        }
+        self.step_powers(slow);
+        self.catch_worms();
+        self.collide_worms();
+        for p in 0..self.players.len() {
+            self.collide_self(p);
+        }
+        self.index_units(); // bites, steals and drops have moved units around
+        self.block_foods();
+        self.drop_spare_foods();
+    }
+
+    fn create_foods(&mut self) {
+        self.foods.clear();
+        // the letters shown of each word, as far as it is long, and the predators
+        let n = self.spellings.iter().map(|s| s.letters.len().min(self.letters_shown)).sum::<usize>() + self.predators;
+        for id in 0..n {
+            // apart from the other foods and the heads, and clear of the
+            // obstacles; crowded spawn zones let them overlap after a while,
+            // and one that is all rock takes them anyway
+            let mut pos = self.spawn_point(DIAMETER);
+            for tries in 0..2 * SPAWN_TRIES {
+                let spaced = tries >= SPAWN_TRIES
+                    || (!self.foods.iter().any(|fd| (fd.pos - pos).length() < 2.0 * DIAMETER)
+                        && !self.players.iter().any(|p| (p.worm.head.position - pos).length() < 4.0 * DIAMETER));
+                if spaced && !self.obstacles.iter().any(|o| o.covers(pos, DIAMETER)) {
+                    break;
+                }
+                pos = self.spawn_point(DIAMETER);
+            }
+            self.foods.push(Food::default().set_id(id).set_pos(pos));
+        }
+        for owner in 0..self.spellings.len() {
+            self.rebalance(owner, None);
+        }
+    }
+
+    // Puts the next letters of a word on the foods: letters that are no
+    // longer among the next few go back, missing ones go on free foods,
+    // the preferred one first, or on a new food when none is free.
+    fn rebalance(&mut self, owner: usize, preferred: Option<usize>) {
+        let wanted = self.spellings[owner].wanted(self.letters_shown);
+        for fd in &mut self.foods {
+            if fd.tag.is_some() && fd.owner == owner && !wanted.contains(&fd.seq) {
+                fd.tag = None;
+            }
+        }
+        for seq in wanted {
+            if self.foods.iter().any(|f| f.tag.is_some() && f.owner == owner && f.seq == seq) {
+                continue;
+            }
+            let free = preferred
+                .filter(|&i| self.foods[i].tag.is_none())
+                .or_else(|| self.foods.iter().position(|f| f.tag.is_none()));
+            let i = free.unwrap_or_else(|| {
+                let id = self.foods.iter().map(|f| f.id + 1).max().unwrap_or(0);
+                let food = Food::default().set_id(id).set_pos(self.rand_vec2());
+                self.foods.push(food);
+                self.foods.len() - 1
+            });
+            self.foods[i].tag   = Some(self.spellings[owner].letters[seq]);
+            self.foods[i].owner = owner;
+            self.foods[i].seq   = seq;
+        }
+    }
+
+    // near the end of a word there are fewer letters to show, the foods
+    // that carried them go instead of joining the predators
+    fn drop_spare_foods(&mut self) {
+        let mut spare = self.foods.iter().filter(|f| f.tag.is_none()).count().saturating_sub(self.predators);
+        self.foods.retain(|f| {
+            let drop = f.tag.is_none() && spare > 0;
+            if drop {
+                spare -= 1;
+            }
+            !drop
+        });
+    }
+
+    // Letters knocked off a worm are to be eaten again. They fall where
+    // they were, on the preferred food first, as far as they are among
+    // the next ones of the word.
+    fn put_back(&mut self, owner: usize, units: &[Unit], preferred: Option<usize>) {
+        for seq in units.iter().filter_map(|u| u.seq) {
+            self.spellings[owner].eaten[seq] = false;
+        }
+        self.rebalance(owner, preferred);
+        for u in units {
+            let Some(seq) = u.seq else { continue };
+            if let Some(fd) = self.foods.iter_mut().find(|f| f.tag.is_some() && f.owner == owner && f.seq == seq) {
+                fd.pos = u.position;
+                fd.wag_tail();
+            }
+        }
+    }
+
+    // the first letter with its tail under the head of p; the tail is
+    // FOOD_LEN behind the food, which is what the grid holds
+    fn find_food(&self, p: usize) -> Option<usize> {
+        let head = self.players[p].worm.head.position;
+        self.food_grid.near(head, DIAMETER + FOOD_LEN)
+            .filter(|&i| self.foods[i].tag.is_some() && self.delta(self.foods[i].tag_pos, head).length() <= DIAMETER)
+            .min()
+    }
+
+    // the foods with the letter p needs next, what a hint shows
+    pub fn next_foods(&self, p: usize) -> Vec<&Food> {
+        let owner = self.players[p].spelling;
+        let Some(next) = self.spellings.get(owner).and_then(|s| s.next_letter()) else { return Vec::new() };
+        self.foods.iter().filter(|fd| fd.tag == Some(next) && fd.owner == owner).collect()
+    }
+
+    // the next letter of a worm with a magnet drifts toward its head
+    fn pull_letters(&mut self) {
+        let wrap = self.wrap.then_some(self.size);
+        for p in self.players.iter().filter(|p| p.has(Power::Magnet)) {
+            let Some(next) = self.spellings.get(p.spelling).and_then(|s| s.next_letter()) else { continue };
+            let head = p.worm.head.position;
+            for fd in self.foods.iter_mut().filter(|fd| fd.tag == Some(next) && fd.owner == p.spelling) {
+                let d = shortest(head - fd.tag_pos, wrap);
+                if d.length() < MAGNET_RADIUS && d.length() > 0.0 {
+                    fd.pos += MAGNET_PULL * d.normalized();
+                }
+            }
+        }
+    }
+
+    // Every so often a power-up turns up. It drifts about until a head
+    // eats it or it fades away.
+    fn step_powers(&mut self, slow: f32) {
+        self.power_timer += 1;
+        if self.power_timer >= POWER_EVERY && self.powers.len() < MAX_POWERS && !self.power_kinds.is_empty() {
+            self.power_timer = 0;
+            let mut rng = rand::thread_rng();
+            let mut fd = Food::default().set_pos(self.rand_vec2());
+            fd.power = Some(self.power_kinds[rng.gen_range(0..self.power_kinds.len())]);
+            fd.life  = POWER_LIFE;
+            fd.angle = rng.gen_range(-PI..PI);
+            self.powers.push(fd);
+        }
+
+        for fd in &mut self.powers {
+            fd.speed = 0.5 * slow * FOOD_SPEED;
+            fd.move_me(self.size, self.wrap);
+            for o in &self.obstacles {
+                if let Some(push) = o.push(fd.pos, HALF_SIZE) {
+                    bounce(fd, push);
+                }
+            }
+            fd.life = fd.life.saturating_sub(1);
+        }
+        self.powers.retain(|fd| fd.life > 0);
+
+        for p in 0..self.players.len() {
+            let head = self.players[p].worm.head.position;
+            let Some(i) = self.powers.iter().position(|fd| self.delta(fd.pos, head).length() < DIAMETER) else { continue };
+            let Some(power) = self.powers.remove(i).power else { continue };
+            self.players[p].give(power);
+            self.events.push(Event::PowerUp { player: p, power });
+        }
+    }
+
+    // The free foods bite the worms anywhere. A bite cuts the worm there
+    // and the letters behind it fall off; a bite into the plain units at
+    // the front costs all the letters, and a worm without letters loses
+    // a unit.
+    fn catch_worms(&mut self) {
+        for id in 0..self.foods.len() {
+            if self.foods[id].tag.is_some() {
+                continue;
+            }
+            let Some((p, i, _)) = self.nearest_unit(self.foods[id].pos, TOUCH) else { continue };
+            if self.players[p].has(Power::Shield) {
+                continue;
+            }
+
+            let units = &self.players[p].worm.units;
+            let plain = units.iter().take_while(|u| u.seq.is_none()).count();
+            let at = i.max(plain).min(units.len() - 1);
+            let cut = self.players[p].worm.units.split_off(at);
+            let owner = self.players[p].spelling;
+            self.put_back(owner, &cut, Some(id));
+            self.index_units();
+            if self.players[p].worm.units.len() < 2 {
+                self.players[p].worm.head.velocity = vec2(0.0, 0.0);
+                self.events.push(Event::Lost { player: p });
+                break;
+            }
+        }
+    }
+
+    fn handling_caught(&mut self, p: usize, idx: usize) {
+        let owner = self.players[p].spelling;
+        let sp = &self.spellings[owner];
+        let Some(next) = sp.next() else { return };
+        if self.foods[idx].owner != owner || self.foods[idx].tag != Some(sp.letters[next]) {
+            return;
+        }
+        let letter = sp.letters[next];
+
+        // the same letter twice in a word: this food takes the place of the next one
+        let seq = self.foods[idx].seq;
+        if seq != next {
+            if let Some(j) = self.foods.iter().position(|f| f.tag.is_some() && f.owner == owner && f.seq == next) {
+                self.foods[j].seq = seq;
+            }
+            self.foods[idx].seq = next;
+        }
+
+        self.spellings[owner].eaten[next] = true;
+        self.players[p].worm.grow(letter, Some(next));
+        self.players[p].score += 1;
+        self.players[p].target = None;
+        self.events.push(Event::Caught { letter });
+
+        self.rebalance(owner, Some(idx));
+        let pos = self.rand_vec2();
+        self.foods[idx].pos = pos;
+        self.foods[idx].tag_pos = Vec2 {
+            x: pos.x - FOOD_LEN,
+            y: pos.y,
+        };
+
+        if self.spellings[owner].done() { // winning
+            self.events.push(Event::WordDone { player: p });
+        }
+    }
+
+    // Heads bounce off each other and off other worms. A head touching the
+    // tail letter of another worm steals it; with own words the letter goes
+    // back into the arena instead, since it belongs to the other word.
+    fn collide_worms(&mut self) {
+        let n = self.players.len();
+        for a in 0..n {
+            for b in 0..n {
+                if a == b {
+                    continue;
+                }
+                let head = self.players[a].worm.head.position;
+
+                if a < b {
+                    let seg = self.delta(head, self.players[b].worm.head.position);
+                    if seg.length() < 2.0 * R_LEAD {
+                        let normal = seg.normalized();
+                        let va = self.players[a].worm.head.velocity;
+                        let vb = self.players[b].worm.head.velocity;
+                        let dv = (va - vb).dot(normal) * normal;
+                        self.players[a].worm.head.velocity -= dv;
+                        self.players[b].worm.head.velocity += dv;
+                        self.players[a].worm.head.position += 0.5 * (2.0 * R_LEAD - seg.length()) * normal;
+                        self.players[b].worm.head.position -= 0.5 * (2.0 * R_LEAD - seg.length()) * normal;
+                    }
+                }
+
+                let last = self.players[b].worm.units.len() - 1;
+                for i in 0..=last {
+                    let seg = self.delta(head, self.players[b].worm.units[i].position);
+                    if seg.length() >= R_LEAD + R_UNIT {
+                        continue;
+                    }
+                    let tail_letter = i == last && self.players[b].worm.units[i].seq.is_some();
+                    if tail_letter && self.players[a].cooldown == 0 {
+                        self.steal(a, b);
+                    } else {
+                        let normal = seg.normalized();
+                        let v = self.players[a].worm.head.velocity;
+                        if v.dot(normal) < 0.0 {
+                            self.players[a].worm.head.velocity = v - 2.0 * v.dot(normal) * normal;
+                        }
+                    }
+                    break;
+                }
+            }
+        }
+    }
+
+    // the head bounces off the worm's own body, the first units excepted
+    fn collide_self(&mut self, p: usize) {
+        let wrap = self.wrap.then_some(self.size);
+        let worm = &mut self.players[p].worm;
+        let head = worm.head.position;
+        let hit = worm.units.iter().skip(SELF_SKIP)
+            .map(|u| shortest(head - u.position, wrap))
+            .find(|seg| seg.length() < R_LEAD + R_UNIT);
+        let Some(seg) = hit else { return };
+
+        let normal = seg.normalized();
+        let v = worm.head.velocity;
+        if v.dot(normal) < 0.0 {
+            worm.head.velocity = v - 2.0 * v.dot(normal) * normal;
+        }
+
+        let tail_letter = worm.units.last().and_then(|u| u.seq);
+        if !self.self_penalty || self.players[p].cooldown > 0 {
+            return;
+        }
+        if tail_letter.is_some() {
+            let unit = self.players[p].worm.units.pop().unwrap();
+            self.players[p].score = self.players[p].score.saturating_sub(1);
+            self.players[p].cooldown = STEAL_COOLDOWN;
+            let owner = self.players[p].spelling;
+            self.put_back(owner, &[unit], None);
+            self.events.push(Event::Dropped);
+        }
+    }
+
+    // Foods cannot swim through a worm: they bounce off its units, so a
+    // long worm can fence them in. Predators go right through, to bite,
+    // unless the worm has a shield.
+    fn block_foods(&mut self) {
+        let wrap = self.wrap.then_some(self.size);
+        for fd in &mut self.foods {
+            for (p, i) in self.unit_grid.near(fd.pos, R_UNIT + HALF_SIZE) {
+                let pl = &self.players[p];
+                if fd.tag.is_none() && !pl.has(Power::Shield) {
+                    continue;
+                }
+                let seg = shortest(fd.pos - pl.worm.units[i].position, wrap);
+                let dist = seg.length();
+                if dist < R_UNIT + HALF_SIZE && dist > 0.0 {
+                    bounce(fd, (R_UNIT + HALF_SIZE - dist) * seg / dist);
+                }
+            }
+        }
+    }
+
+    fn steal(&mut self, by: usize, from: usize) {
+        let unit = self.players[from].worm.units.pop().unwrap();
+        let seq = unit.seq.unwrap();
+        self.players[from].score = self.players[from].score.saturating_sub(1);
+        self.players[by].cooldown = STEAL_COOLDOWN;
+        let owner = self.players[from].spelling;
+        if owner == self.players[by].spelling {
+            self.players[by].worm.grow(unit.letter, Some(seq));
+            self.players[by].score += 1;
+        } else {
+            self.put_back(owner, &[unit], None);
+        }
+        self.events.push(Event::Stolen);
+    }
+
+    pub fn index_foods(&mut self) {
+        self.food_grid.reset(self.size, self.wrap);
+        for (i, fd) in self.foods.iter().enumerate() {
+            self.food_grid.insert(fd.pos, i);
+        }
+    }
+
+    fn index_units(&mut self) {
+        self.unit_grid.reset(self.size, self.wrap);
+        for (p, pl) in self.players.iter().enumerate() {
+            for (i, u) in pl.worm.units.iter().enumerate() {
+                self.unit_grid.insert(u.position, (p, i));
+            }
+        }
+    }
+
+    // the other foods within radius of the food at i
+    pub fn foods_near(&self, i: usize, radius: f32) -> impl Iterator<Item = usize> + '_ {
+        let pos = self.foods[i].pos;
+        self.food_grid.near(pos, radius)
+            .filter(move |&j| j != i && self.delta(self.foods[j].pos, pos).length() < radius)
+    }
+
+    // the unit of any worm closest to pos within radius: the player, the
+    // unit and the way from pos to it
+    pub fn nearest_unit(&self, pos: Vec2, radius: f32) -> Option<(usize, usize, Vec2)> {
+        self.unit_grid.near(pos, radius)
+            .map(|(p, i)| (p, i, self.delta(self.players[p].worm.units[i].position, pos)))
+            .filter(|u| u.2.length() < radius)
+            .min_by(|a, b| a.2.length().total_cmp(&b.2.length()))
+    }
+
+    // the creature the food at i is, letter or not
+    pub fn kind(&self, i: usize) -> &Creature {
+        if self.foods[i].tag.is_some() { &self.letter_kind } else { &self.predator_kind }
+    }
+
+    // from b to a, across the edge if that is shorter
+    pub fn delta(&self, a: Vec2, b: Vec2) -> Vec2 {
+        shortest(a - b, self.wrap.then_some(self.size))
+    }
+
+    // a random point of a random spawn zone, or of the arena
+    fn spawn_point(&self, margin: f32) -> Vec2 {
+        let mut rng = rand::thread_rng();
+        let arena = Rect::from_min_size(egui::Pos2::ZERO, self.size);
+        let zone = if self.spawns.is_empty() {
+            arena
+        } else {
+            self.spawns[rng.gen_range(0..self.spawns.len())].intersect(arena)
+        };
+        let zone = zone.shrink(margin);
+        if zone.width() <= 0.0 || zone.height() <= 0.0 {
+            return zone.center().to_vec2();
+        }
+        vec2(
+            rng.gen_range(zone.min.x..zone.max.x),
+            rng.gen_range(zone.min.y..zone.max.y),
+        )
+    }
+
+    fn rand_vec2(&mut self) -> Vec2 {
+        let mut pos = Vec2 { x: 0.0, y: 0.0 };
+        for _ in 0..10 { // try ten times
+            pos = self.spawn_point(MIN_DIST);
+            let overlap = self.foods.iter().any(|fd| (fd.pos - pos).length() < 2.0 * MIN_DIST)
+                || self.players.iter().any(|p| (p.worm.head.position - pos).length() < 2.0 * MIN_DIST)
+                || self.obstacles.iter().any(|o| o.covers(pos, MIN_DIST));
+            if !overlap {
+                break;
+            }
+        }
+        pos
+    }
+}
+
+fn shortest(d: Vec2, wrap: Option<Vec2>) -> Vec2 {
+    match wrap {
+        Some(size) => wrap_delta(d, size),
+        None => d,
+    }
+}
+
+// moves a food out by push and turns it away
+fn bounce(fd: &mut Food, push: Vec2) {
+    fd.pos += push;
+    let normal = push.normalized();
+    let dir = Vec2::angled(fd.angle);
+    if dir.dot(normal) < 0.0 {
+        fd.angle = (dir - 2.0 * dir.dot(normal) * normal).angle();
+    }
+}
@@ -7,24 +7,35 @@ use egui::{
 
 use crate::lead::Lead;
 use crate::unit::Unit;
+use crate::level::Obstacle;
+use crate::body::BodyModel;
 
 use crate::consts::{
     R_LEAD,
     R_UNIT,
     L0_LEAD,
     L0_UNIT,
+    MS_NECK,
+    KF_NECK,
+    KS_NECK,
+    PALETTES,
+    WATER_DRAG,
+    DT,
+    MAX_FRAMES,
     calc_hooke_force,
+    wrap_delta,
+    wrap_pos,
 };
 
-const PURPLE2: Color32   = Color32::from_rgb(190, 79, 233);
-const PURPLE3: Color32   = Color32::from_rgb(208,106, 230);
-
 
 pub struct Worm {
     pub head:  Lead,
     pub neck:  Lead,
     pub units: Vec<Unit>,
-    pub soft_mode: bool,
+    pub body:  usize, // which of the world's body models moves the units
+    pub constraints: bool, // links kept at their rest length on top of the springs
+    pub l0_lead: f32,      // head to neck
+    pub palette:   [Color32; 3],
 }
 
 impl Default for Worm {
@@ -33,55 +44,78 @@ impl Default for Worm {
             head:  Lead::default(),
             neck:  Lead::default(),
             units: vec![],
-            soft_mode: true,
+            body:  0,
+            constraints: false,
+            l0_lead: L0_LEAD,
+            palette:   PALETTES[0],
         }
     }
 }
 
 impl Worm {
 
-    pub fn reset(&mut self) {
+    pub fn reset(&mut self, start: Vec2, palette: [Color32; 3]) {
         self.units.clear();
+        self.palette = palette;
         self.head = Lead::default();
+        self.head.position = start;
+        let y = start.y;
+        self.head.color = self.color();
         self.neck = Lead::default();
-        self.neck.set_params(0.10, 0.15, 0.06);
-        self.neck.position.x = self.head.position.x - L0_LEAD;
+        self.neck.set_params(MS_NECK, KF_NECK, KS_NECK);
+        self.neck.position = vec2(self.head.position.x - self.l0_lead, y);
         self.neck.radius   = (R_LEAD+R_UNIT)/2.0;
+        self.neck.color = self.color();
 
-        let mut u  = Unit::default();
-        u.color    = PURPLE2;
-        u.position.x = self.head.position.x - L0_LEAD - L0_UNIT;
-        self.units.push(u);
+        for (i, color) in palette.into_iter().enumerate().skip(1) {
+            let mut u  = Unit::default();
+            u.color    = color;
+            u.position = vec2(self.head.position.x - self.l0_lead - i as f32 * L0_UNIT, y);
+            self.units.push(u);
+        }
+    }
 
-        let mut u  = Unit::default();
-        u.color    = PURPLE3;
-        u.position.x = self.head.position.x - L0_LEAD - 2.0*L0_UNIT;
-        self.units.push(u);
+    // head colour, dark red in all but the first body model
+    pub fn color(&self) -> Color32 {
+        if self.body == 0 { self.palette[0] } else { Color32::DARK_RED }
     }
 
-    pub fn drive_me(&mut self, f: Vec2) {
-        self.head.drive_me(f);
-        let f = calc_hooke_force(self.head.position - self.neck.position, L0_LEAD, self.neck.ks);
-        self.neck.drive_me(f);
+    pub fn toggle_mode(&mut self, n_models: usize) {
+        self.body = (self.body + 1) % n_models;
+        self.head.color = self.color();
+        self.neck.color = self.color();
+    }
 
-        if self.soft_mode {
-            let mut p_lead = self.neck.position;
-            for seg in &mut self.units {
-                seg.pull_me(p_lead);
-                p_lead = seg.position;
+    // the time since the last step, in frames, driven in substeps of at
+    // most DT so that stiff springs and long worms stay stable
+    pub fn drive_me(&mut self, f: Vec2, body: &mut dyn BodyModel, frames: f32) {
+        let frames = frames.clamp(DT, MAX_FRAMES);
+        let n = (frames / DT).ceil() as usize;
+        let dt = frames / n as f32;
+        for _ in 0..n {
+            self.head.drive_me(f, dt);
+            let f = calc_hooke_force(self.head.position - self.neck.position, self.l0_lead, self.neck.ks);
+            self.neck.drive_me(f, dt);
+            body.follow(self.neck.position, &mut self.units, dt);
+            if self.constraints {
+                self.constrain();
             }
-        } else {
-            let ang = (self.neck.position - self.units[0].position).angle();
-            let xt = self.neck.position.x - L0_UNIT * ang.cos();
-            let yt = self.neck.position.y - L0_UNIT * ang.sin();
-
-            let mut p_target = vec2(xt, yt);
-            let mut p_lead = self.neck.position;
+        }
+    }
 
-            for seg in &mut self.units {
-                p_target = seg.move_me(p_target, p_lead);
-                p_lead   = seg.position;
-            }
+    // position based: each segment is put back at its rest length from the
+    // one ahead of it
+    fn constrain(&mut self) {
+        let fix = |pos: Vec2, lead: Vec2, l0: f32| {
+            let d = pos - lead;
+            let len = d.length();
+            if len > 0.0 { lead + d * (l0 / len) } else { pos }
+        };
+        self.neck.position = fix(self.neck.position, self.head.position, self.l0_lead);
+        let mut lead = self.neck.position;
+        for u in &mut self.units {
+            u.position = fix(u.position, lead, u.l0);
+            lead = u.position;
         }
     }
 
@@ -94,10 +128,58 @@ impl Worm {
         } 
     }
 
-    pub fn grow(&mut self, letter: char) {
+    // rocks and walls push the chain out and the head bounces off; water slows it
+    pub fn hit_obstacles(&mut self, obstacles: &[Obstacle]) {
+        for o in obstacles {
+            if let Some(push) = o.push(self.head.position, self.head.radius) {
+                self.head.position += push;
+                let normal = push.normalized();
+                let v = self.head.velocity;
+                if v.dot(normal) < 0.0 {
+                    self.head.velocity = v - 1.8 * v.dot(normal) * normal;
+                }
+            }
+            if let Some(push) = o.push(self.neck.position, self.neck.radius) {
+                self.neck.position += push;
+            }
+            for u in &mut self.units {
+                if let Some(push) = o.push(u.position, R_UNIT) {
+                    u.position += push;
+                    let normal = push.normalized();
+                    u.velocity -= u.velocity.dot(normal).min(0.0) * normal;
+                }
+            }
+            if o.is_water_at(self.head.position) {
+                self.head.velocity *= WATER_DRAG;
+            }
+        }
+    }
+
+    // In a wrapping arena each segment is moved next to the one ahead of
+    // it before the springs pull, and put back inside the arena after.
+    pub fn unwrap(&mut self, size: Vec2) {
+        let head = self.head.position;
+        self.neck.position = head + wrap_delta(self.neck.position - head, size);
+        let mut lead = self.neck.position;
+        for u in &mut self.units {
+            u.position = lead + wrap_delta(u.position - lead, size);
+            lead = u.position;
+        }
+    }
+
+    pub fn wrap(&mut self, size: Vec2) {
+        self.head.position = wrap_pos(self.head.position, size);
+        self.neck.position = wrap_pos(self.neck.position, size);
+        for u in &mut self.units {
+            u.position = wrap_pos(u.position, size);
+        }
+    }
+
+    pub fn grow(&mut self, letter: char, seq: Option<usize>) {
         let n = self.units.len();
         let mut u = Unit::default();
         u.letter = letter;
+        u.seq = seq;
         u.position = 2.0 * self.units[n-1].position - self.units[n-2].position;
         self.units.push(u);
     }